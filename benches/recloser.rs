@@ -2,7 +2,6 @@ use std::time::Duration;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use fake_clock::FakeClock;
-use num_cpus;
 use rayon::prelude::*;
 
 use failsafe::{backoff, failure_policy, CircuitBreaker, Config};
@@ -15,7 +14,7 @@ fn sleep(time: u64) {
 }
 
 fn dangerous_call(n: u64) -> Result<u64, u64> {
-    if n % 5 == 0 {
+    if n.is_multiple_of(5) {
         black_box(Err(n))
     } else {
         black_box(Ok(n))
@@ -40,7 +39,7 @@ fn make_failsafe() -> impl CircuitBreaker {
 fn recloser_simple() {
     let recloser = make_recloser();
 
-    (0..ITER_C).into_iter().for_each(|i| {
+    (0..ITER_C).for_each(|i| {
         match recloser.call(|| dangerous_call(i)) {
             Ok(_) => {}
             Err(recloser::Error::Inner(_)) => {}
@@ -53,7 +52,7 @@ fn recloser_simple() {
 fn failsafe_simple() {
     let circuit_breaker = make_failsafe();
 
-    (0..ITER_C).into_iter().for_each(|i| {
+    (0..ITER_C).for_each(|i| {
         match circuit_breaker.call(|| dangerous_call(i)) {
             Ok(_) => {}
             Err(failsafe::Error::Inner(_)) => {}
@@ -78,6 +77,20 @@ fn recloser_concurrent() {
         });
 }
 
+/// Stays in `Closed(_)` state, mostly measures contention on the `RingBuffer`.
+fn recloser_contended(closed_len: usize) {
+    let recloser = Recloser::custom()
+        .error_rate(1.0)
+        .closed_len(closed_len)
+        .build();
+
+    (0..ITER_C * num_cpus::get() as u64)
+        .into_par_iter()
+        .for_each(|i| {
+            let _ = black_box(recloser.call(|| dangerous_call(i)));
+        });
+}
+
 fn failsafe_concurrent() {
     let circuit_breaker = make_failsafe();
 
@@ -94,10 +107,16 @@ fn failsafe_concurrent() {
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("recloser_simple", |b| b.iter(|| recloser_simple()));
-    c.bench_function("failsafe_simple", |b| b.iter(|| failsafe_simple()));
-    c.bench_function("recloser_concurrent", |b| b.iter(|| recloser_concurrent()));
-    c.bench_function("failsafe_concurrent", |b| b.iter(|| failsafe_concurrent()));
+    c.bench_function("recloser_simple", |b| b.iter(recloser_simple));
+    c.bench_function("failsafe_simple", |b| b.iter(failsafe_simple));
+    c.bench_function("recloser_concurrent", |b| b.iter(recloser_concurrent));
+    c.bench_function("failsafe_concurrent", |b| b.iter(failsafe_concurrent));
+    c.bench_function("recloser_contended_1000", |b| {
+        b.iter(|| recloser_contended(1000))
+    });
+    c.bench_function("recloser_contended_1024", |b| {
+        b.iter(|| recloser_contended(1024))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,24 @@
+//! Classifies `http::Response`s, for middlewares that get failed responses as `Ok(_)`.
+//!
+//! Run with `cargo run --example http --features http,tokio`.
+
+use recloser::{AsyncRecloser, Recloser, StatusClassifier};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+
+    for _ in 0..2 {
+        // Stands for the response of an HTTP client
+        let request = async {
+            http::Response::builder()
+                .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                .body(())
+        };
+        let res = recloser
+            .call_classified(StatusClassifier::new(), request)
+            .await;
+        println!("{:?}", res.map(|res| res.status()));
+    }
+    println!("{:?}", recloser.state());
+}
@@ -0,0 +1,17 @@
+//! Checks the invariants of arbitrary configurations against arbitrary calls.
+//!
+//! Run with `cargo run --example invariants --features proptest`, usually written as a
+//! `proptest!` test instead.
+
+use proptest::prelude::*;
+use recloser::{check_invariants, steps, RecloserConfig};
+
+fn main() {
+    let runner = ProptestConfig {
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    };
+    proptest!(runner, |(config in any::<RecloserConfig>(), steps in steps(0..200))| {
+        check_invariants(&config.builder().build(), &steps)?;
+    });
+}
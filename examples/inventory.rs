@@ -0,0 +1,13 @@
+//! Declares a `Recloser` that registers itself into `Registry::global()` at startup.
+//!
+//! Run with `cargo run --example inventory --features inventory`.
+
+use recloser::{register_recloser, Recloser, Registry};
+
+register_recloser!("payments", Recloser::custom().error_rate(0.3));
+
+fn main() {
+    let registry = Registry::global();
+    assert!(registry.state("payments").is_some());
+    println!("{registry:?}");
+}
@@ -0,0 +1,16 @@
+//! Guards the requests of an `async_nats::Client`.
+//!
+//! Run with `cargo run --example nats --features async-nats,tokio`.
+
+use recloser::{AsyncRecloser, GuardedClient, Recloser};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), async_nats::ConnectError> {
+    let client = async_nats::connect("demo.nats.io").await?;
+    let client = GuardedClient::new(client, AsyncRecloser::from(Recloser::default()));
+
+    let res = client.request("service", "data".into()).await;
+    println!("{res:?}");
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! Records a trace of calls, then replays it against another configuration.
+//!
+//! Run with `cargo run --example replay --features trace`.
+
+use std::io::BufReader;
+
+use recloser::{read_trace, Recloser, TraceRecorder};
+
+fn main() -> std::io::Result<()> {
+    // Usually written to a file, such as `payments.trace`
+    let recorder = TraceRecorder::new(Vec::new());
+    let recloser = Recloser::default();
+    for i in 0..100 {
+        let _ = recorder.call(&recloser, || if i % 3 == 0 { Err(i) } else { Ok(i) });
+    }
+
+    let trace = recorder.into_inner();
+    let events = read_trace(BufReader::new(trace.as_slice()))?;
+    let replay = Recloser::custom()
+        .closed_len(10)
+        .error_rate(0.3)
+        .replay(events);
+    println!("{} trips, {} rejected calls", replay.trips, replay.rejected);
+
+    Ok(())
+}
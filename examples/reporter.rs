@@ -0,0 +1,23 @@
+//! Pushes the `Snapshot`s of the registered `Recloser`s periodically.
+//!
+//! Run with `cargo run --example reporter --features tokio`.
+
+use std::time::Duration;
+
+use recloser::{AsyncRecloser, Recloser, Registry, Snapshot};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let registry = Registry::global();
+    registry.register("payments", AsyncRecloser::from(Recloser::default()));
+
+    let handle = registry.spawn_reporter(
+        Duration::from_secs(1),
+        |name: Option<&str>, snapshot: &Snapshot| {
+            println!("{name:?}: {snapshot:?}");
+        },
+    );
+
+    tokio::time::sleep(Duration::from_millis(3500)).await;
+    handle.abort();
+}
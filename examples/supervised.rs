@@ -0,0 +1,33 @@
+//! Supervises a background job with an `AsyncRecloser`, and awaits its state changes.
+//!
+//! Run with `cargo run --example supervised --features tokio`.
+
+use std::time::Duration;
+
+use recloser::{AsyncRecloser, CircuitState, Recloser};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let recloser = AsyncRecloser::from(
+        Recloser::custom()
+            .closed_len(4)
+            .open_wait(Duration::from_secs(1))
+            .build(),
+    );
+
+    // Some background job that always fails, the breaker trips after a few attempts
+    let handle = recloser.spawn_supervised(|| async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Err::<(), _>(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+    });
+
+    // Awaits the state changes rather than polling
+    let mut state = recloser.state_watch();
+    state
+        .wait_for(|state| *state == CircuitState::Open)
+        .await
+        .unwrap();
+    println!("{:?}", recloser.handle().rejection());
+
+    handle.abort();
+}
@@ -0,0 +1,44 @@
+//! Guards every method of a `tarpc` client, only considering exceeded deadlines as
+//! failures.
+//!
+//! Run with `cargo run --example tarpc --features tarpc,tokio`.
+
+use futures::prelude::*;
+use recloser::{AsyncRecloser, GuardedStub, Recloser, Timeouts};
+use tarpc::server::{self, Channel};
+use tarpc::{client, context};
+
+#[tarpc::service]
+trait World {
+    async fn hello(name: String) -> String;
+}
+
+#[derive(Clone)]
+struct HelloServer;
+
+impl World for HelloServer {
+    async fn hello(self, _: context::Context, name: String) -> String {
+        format!("Hello, {name}!")
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let (client_transport, server_transport) = tarpc::transport::channel::unbounded();
+    let server = server::BaseChannel::with_defaults(server_transport);
+    tokio::spawn(
+        server
+            .execute(HelloServer.serve())
+            .for_each(|response| async {
+                tokio::spawn(response);
+            }),
+    );
+
+    let channel = client::new(client::Config::default(), client_transport);
+    tokio::spawn(channel.dispatch);
+
+    let recloser = AsyncRecloser::from(Recloser::custom().default_predicate(Timeouts).build());
+    let client = WorldClient::from(GuardedStub::new(channel.client, recloser));
+    let hello = client.hello(context::current(), "Stim".into()).await;
+    println!("{hello:?}");
+}
@@ -0,0 +1,13 @@
+//! Guards a blocking `ureq::Agent`, considering `408` responses as failures too.
+//!
+//! Run with `cargo run --example ureq --features ureq`.
+
+use recloser::{GuardedAgent, Recloser, StatusClassifier};
+
+fn main() {
+    let agent = GuardedAgent::new(ureq::Agent::new_with_defaults(), Recloser::default())
+        .classifier(StatusClassifier::new().fail_on(408..=408));
+
+    let res = agent.call(|agent| agent.get("http://example.com").call());
+    println!("{:?}", res.map(|res| res.status()));
+}
@@ -0,0 +1,228 @@
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::{HeaderMap, Method, Request, Response, StatusCode};
+use serde::Serialize;
+
+use crate::recloser::Snapshot;
+use crate::registry::Registry;
+
+type AuthHook = Arc<dyn Fn(&HeaderMap) -> bool + Send + Sync>;
+
+/// A `tower::Service` exposing a tiny JSON API to inspect and control the `Recloser`s
+/// of a `Registry`:
+///
+/// - `GET /breakers`: lists all the breakers along with their `Snapshot`.
+/// - `GET /breakers/{name}`: returns the `Snapshot` of a single breaker.
+/// - `POST /breakers/{name}/trip`, `POST /breakers/{name}/force-open`,
+///   `POST /breakers/{name}/force-close` and `POST /breakers/{name}/reset`: controls a
+///   breaker, returns its new `Snapshot`.
+///
+/// Being a `tower::Service`, it can be mounted as is in an `axum` (or `hyper`) server:
+///
+/// ```rust,ignore
+/// use std::sync::Arc;
+/// use recloser::{AdminService, Registry};
+///
+/// let registry = Arc::new(Registry::new());
+/// let admin = AdminService::new(registry)
+///     .auth(|headers| headers.get("authorization").is_some_and(|v| v == "Bearer secret"));
+/// let app = axum::Router::new().fallback_service(admin);
+/// ```
+#[derive(Clone)]
+pub struct AdminService {
+    registry: Arc<Registry>,
+    auth: Option<AuthHook>,
+}
+
+#[derive(Serialize)]
+struct Status<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    snapshot: Snapshot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trip_error: Option<String>,
+}
+
+impl AdminService {
+    pub fn new(registry: Arc<Registry>) -> Self {
+        AdminService {
+            registry,
+            auth: None,
+        }
+    }
+
+    /// Only serves requests whose headers are accepted by `f`, others are
+    /// answered with `401 Unauthorized`.
+    pub fn auth<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HeaderMap) -> bool + Send + Sync + 'static,
+    {
+        self.auth = Some(Arc::new(f));
+        self
+    }
+
+    fn handle(&self, method: &Method, path: &str, headers: &HeaderMap) -> Response<String> {
+        if let Some(auth) = &self.auth {
+            if !auth(headers) {
+                return error(StatusCode::UNAUTHORIZED, "unauthorized");
+            }
+        }
+
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        match (method, segments.as_slice()) {
+            (&Method::GET, ["breakers"]) => {
+                let snapshots = self.registry.snapshots();
+                let statuses = snapshots
+                    .iter()
+                    .map(|(name, snapshot)| Status {
+                        name,
+                        snapshot: *snapshot,
+                        trip_error: self.registry.trip_error(name),
+                    })
+                    .collect::<Vec<_>>();
+                json(StatusCode::OK, &statuses)
+            }
+            (&Method::GET, ["breakers", name]) => self.status(name),
+            (&Method::POST, ["breakers", name, command]) => {
+                let found = match *command {
+                    "trip" => self.registry.trip(name),
+                    "force-open" => self.registry.force_open(name),
+                    "force-close" => {
+                        self.registry.force_close(name);
+                        self.registry.state(name).is_some()
+                    }
+                    "reset" => self.registry.reset(name),
+                    _ => return error(StatusCode::NOT_FOUND, "unknown command"),
+                };
+                if found {
+                    self.status(name)
+                } else {
+                    error(StatusCode::NOT_FOUND, "unknown breaker")
+                }
+            }
+            (_, ["breakers", ..]) => error(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+            _ => error(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    fn status(&self, name: &str) -> Response<String> {
+        match self.registry.snapshot(name) {
+            Some(snapshot) => {
+                let trip_error = self.registry.trip_error(name);
+                json(
+                    StatusCode::OK,
+                    &Status {
+                        name,
+                        snapshot,
+                        trip_error,
+                    },
+                )
+            }
+            None => error(StatusCode::NOT_FOUND, "unknown breaker"),
+        }
+    }
+}
+
+impl std::fmt::Debug for AdminService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminService")
+            .field("registry", &self.registry)
+            .field("auth", &self.auth.is_some())
+            .finish()
+    }
+}
+
+impl<B> tower::Service<Request<B>> for AdminService {
+    type Response = Response<String>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        ready(Ok(self.handle(
+            req.method(),
+            req.uri().path(),
+            req.headers(),
+        )))
+    }
+}
+
+fn json<T: Serialize>(status: StatusCode, body: &T) -> Response<String> {
+    let mut res = Response::new(serde_json::to_string(body).unwrap_or_default());
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+fn error(status: StatusCode, message: &str) -> Response<String> {
+    json(status, &serde_json::json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::Service;
+
+    use super::*;
+    use crate::recloser::Recloser;
+
+    fn send(admin: &mut AdminService, method: Method, path: &str) -> Response<String> {
+        let req = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("authorization", "secret")
+            .body(())
+            .unwrap();
+        admin.call(req).into_inner().unwrap()
+    }
+
+    #[test]
+    fn admin_commands() {
+        let registry = Arc::new(Registry::new());
+        registry.register("payments", Arc::new(Recloser::default()));
+
+        let mut admin = AdminService::new(registry.clone())
+            .auth(|headers| headers.contains_key("authorization"));
+        let field = |res: &Response<String>, path: &str| {
+            let body = serde_json::from_str::<serde_json::Value>(res.body()).unwrap();
+            body.pointer(path).cloned().unwrap_or_default()
+        };
+
+        let res = send(&mut admin, Method::POST, "/breakers/payments/force-open");
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!("payments", field(&res, "/name"));
+        assert_eq!("forced_open", field(&res, "/state"));
+
+        let res = send(&mut admin, Method::GET, "/breakers");
+        assert_eq!("payments", field(&res, "/0/name"));
+        assert_eq!("forced_open", field(&res, "/0/state"));
+        assert_eq!(0.5, field(&res, "/0/threshold"));
+        assert_eq!(0, field(&res, "/0/metrics/calls"));
+
+        let res = send(&mut admin, Method::POST, "/breakers/payments/reset");
+        assert!(res.body().starts_with(
+            r#"{"name":"payments","state":"closed","failure_rate":null,"retry_after":null,"threshold":0.5,"metrics":{"calls":0,"successes":0,"failures":0,"rejected":0,"in_flight":0,"failure_rate":null},"flaps":0,"taken_at":"#
+        ));
+
+        let res = send(&mut admin, Method::POST, "/breakers/payments/trip");
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!("open", field(&res, "/state"));
+
+        let res = send(&mut admin, Method::POST, "/breakers/unknown/reset");
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+        let res = send(&mut admin, Method::DELETE, "/breakers/payments");
+        assert_eq!(StatusCode::METHOD_NOT_ALLOWED, res.status());
+
+        let req = Request::get("/breakers").body(()).unwrap();
+        let res = admin.call(req).into_inner().unwrap();
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+    }
+}
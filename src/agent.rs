@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use ureq::http::Response;
+use ureq::{Agent, Body};
+
+use crate::error::{AnyError, Error};
+use crate::recloser::Recloser;
+use crate::status::StatusClassifier;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// A blocking `ureq::Agent` whose calls are guarded by a `Recloser`, classifying
+/// responses with a `StatusClassifier` rather than with the default predicate.
+///
+/// Transport errors are failures, and so are the responses whose status code is
+/// classified as a failure, whether `ureq` returns them as `Ok(_)` or as
+/// `ureq::Error::StatusCode(_)`.
+///
+/// ```rust,ignore
+/// use recloser::{GuardedAgent, Recloser};
+///
+/// let agent = GuardedAgent::new(ureq::Agent::new_with_defaults(), Recloser::default());
+/// let res = agent.call(|agent| agent.get("http://example.com").call());
+/// ```
+#[derive(Debug)]
+pub struct GuardedAgent<W = DefaultWait, P = AnyError> {
+    agent: Agent,
+    recloser: Arc<Recloser<W, P>>,
+    classifier: StatusClassifier,
+}
+
+/// A response classified as a failure.
+enum Failed {
+    Status(Box<Response<Body>>),
+    Error(ureq::Error),
+}
+
+impl<W: WaitStrategy, P> GuardedAgent<W, P> {
+    pub fn new(agent: Agent, recloser: impl Into<Arc<Recloser<W, P>>>) -> Self {
+        GuardedAgent {
+            agent,
+            recloser: recloser.into(),
+            classifier: StatusClassifier::default(),
+        }
+    }
+
+    pub fn classifier(mut self, classifier: StatusClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    pub fn agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    pub fn recloser(&self) -> &Arc<Recloser<W, P>> {
+        &self.recloser
+    }
+
+    /// Performs the request built by `f` with the wrapped `Agent`, returns
+    /// `Err(Error::Rejected)` without performing it when the `Recloser` is open.
+    pub fn call<F>(&self, f: F) -> Result<Response<Body>, Error<ureq::Error>>
+    where
+        F: FnOnce(&Agent) -> Result<Response<Body>, ureq::Error>,
+    {
+        let classifier = &self.classifier;
+        let predicate = |failed: &Failed| match failed {
+            Failed::Error(ureq::Error::StatusCode(status)) => classifier.is_failure(*status),
+            Failed::Status(_) | Failed::Error(_) => true,
+        };
+
+        let res = self.recloser.call_with(predicate, || match f(&self.agent) {
+            Ok(res) if classifier.is_failure(res.status().as_u16()) => {
+                Err(Failed::Status(Box::new(res)))
+            }
+            Ok(res) => Ok(res),
+            Err(err) => Err(Failed::Error(err)),
+        });
+
+        match res {
+            Ok(res) => Ok(res),
+            Err(Error::Inner(Failed::Status(res))) => Ok(*res),
+            Err(Error::Inner(Failed::Error(err))) => Err(Error::Inner(err)),
+            Err(Error::Rejected) => Err(Error::Rejected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::recloser::CircuitState;
+
+    /// Serves `statuses` to successive connections, returns the server url.
+    fn serve(statuses: Vec<u16>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let res = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                stream.write_all(res.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn guarded_agent() {
+        let url = serve(vec![404, 503, 200, 503]);
+
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .new_agent();
+        let recloser = Recloser::custom().closed_len(2).error_rate(0.5).build();
+        let agent = GuardedAgent::new(agent, recloser);
+
+        let res = agent.call(|agent| agent.get(&url).call());
+        assert_eq!(404, res.unwrap().status().as_u16());
+        let res = agent.call(|agent| agent.get(&url).call());
+        assert_eq!(503, res.unwrap().status().as_u16());
+        let res = agent.call(|agent| agent.get(&url).call());
+        assert_eq!(200, res.unwrap().status().as_u16());
+        assert_eq!(CircuitState::Closed, agent.recloser().state());
+
+        // As an error, with the default configuration of `ureq`
+        let agent = GuardedAgent::new(Agent::new_with_defaults(), Arc::clone(agent.recloser()));
+        let res = agent.call(|agent| agent.get(&url).call());
+        assert!(matches!(
+            res,
+            Err(Error::Inner(ureq::Error::StatusCode(503)))
+        ));
+        assert_eq!(CircuitState::Open, agent.recloser().state());
+        assert!(matches!(
+            agent.call(|agent| agent.get(&url).call()),
+            Err(Error::Rejected)
+        ));
+    }
+}
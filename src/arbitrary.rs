@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use crossbeam_epoch as epoch;
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+
+use crate::config::RecloserConfig;
+use crate::recloser::{CircuitState, Recloser};
+use crate::wait::WaitStrategy;
+
+impl Arbitrary for RecloserConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        (0.01f32..=1.0, 1usize..=200, 1usize..=50, 1u64..=60_000)
+            .prop_flat_map(|(error_rate, closed_len, half_open_len, open_wait)| {
+                (
+                    proptest::option::of(1..=half_open_len),
+                    proptest::option::of(1..=half_open_len),
+                    proptest::option::of(1u64..=120_000),
+                )
+                    .prop_map(
+                        move |(half_open_min_calls, half_open_max_calls, ttl)| RecloserConfig {
+                            error_rate,
+                            closed_len,
+                            half_open_len,
+                            half_open_min_calls,
+                            half_open_max_calls,
+                            half_open_ttl: ttl.map(Duration::from_millis),
+                            open_wait: Duration::from_millis(open_wait),
+                        },
+                    )
+            })
+            .boxed()
+    }
+}
+
+/// A step fed to a `Recloser` by `check_invariants(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// A call that succeeds, if permitted.
+    Success,
+    /// A call that fails, if permitted.
+    Failure,
+    /// Time passing without any call.
+    Elapse(Duration),
+}
+
+impl Arbitrary for Step {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: ()) -> Self::Strategy {
+        prop_oneof![
+            3 => Just(Step::Success),
+            2 => Just(Step::Failure),
+            1 => (1u64..=120_000).prop_map(|ms| Step::Elapse(Duration::from_millis(ms))),
+        ]
+        .boxed()
+    }
+}
+
+/// Generates sequences of `Step`s, with a number of steps in `len`.
+pub fn steps(len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<Step>> {
+    vec(any::<Step>(), len)
+}
+
+/// Feeds `steps` to `recloser` and checks, before each call, the invariants that hold
+/// whatever its configuration:
+///
+/// - calls are never permitted while `Open(_)` before the deadline, nor while `ForcedOpen`,
+/// - calls are always permitted while `Closed(_)`, and while `HalfOpen(_)` unless its
+///   `half_open_ttl(...)` elapsed or its `half_open_max_calls(...)` are all in progress,
+/// - the failure rate of a `Snapshot` is always within `[0, 1]`.
+///
+/// `Elapse(_)` steps move the clock of `recloser` forward, so it should be dedicated
+/// to the test.
+pub fn check_invariants<W: WaitStrategy, P>(
+    recloser: &Recloser<W, P>,
+    steps: &[Step],
+) -> Result<(), TestCaseError> {
+    let guard = &epoch::pin();
+
+    for step in steps {
+        let failed = match step {
+            Step::Elapse(duration) => {
+                recloser.advance(*duration);
+                continue;
+            }
+            Step::Success => false,
+            Step::Failure => true,
+        };
+
+        let state = recloser.state();
+        // Leaves some margin, as the clock keeps running between the two checks
+        let before_deadline = recloser
+            .rejection()
+            .and_then(|rejection| rejection.retry_after)
+            .is_some_and(|retry_after| retry_after > Duration::from_millis(1));
+        let probes_full = recloser.probes_full();
+        let permit = recloser.permit(guard);
+        let permitted = permit.is_some();
+        let expired = state == CircuitState::HalfOpen && recloser.state() != state;
+
+        match state {
+            CircuitState::Open if before_deadline => {
+                prop_assert!(!permitted, "permitted while Open before the deadline")
+            }
+            CircuitState::ForcedOpen => prop_assert!(!permitted, "permitted while ForcedOpen"),
+            CircuitState::HalfOpen if expired || probes_full => (),
+            CircuitState::Closed | CircuitState::HalfOpen => {
+                prop_assert!(permitted, "rejected while {:?}", state)
+            }
+            CircuitState::Open => (),
+        }
+
+        if permitted {
+            match failed {
+                true => recloser.on_error(guard),
+                false => recloser.on_success(guard),
+            }
+        }
+
+        let failure_rate = recloser.snapshot().failure_rate.unwrap_or(0.0);
+        prop_assert!(
+            (0.0..=1.0).contains(&failure_rate),
+            "failure rate {} out of bounds",
+            failure_rate
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Slo;
+
+    proptest! {
+        #[test]
+        fn invariants_hold(config in any::<RecloserConfig>(), steps in steps(0..500)) {
+            check_invariants(&config.builder().build(), &steps)?;
+        }
+    }
+
+    #[test]
+    fn half_open_limits() {
+        let config = RecloserConfig {
+            closed_len: 1,
+            half_open_len: 4,
+            half_open_max_calls: Some(1),
+            half_open_ttl: Some(Duration::from_secs(5)),
+            open_wait: Duration::from_secs(1),
+            ..RecloserConfig::default()
+        };
+        let recloser = config.builder().build();
+
+        let steps = [Step::Failure, Step::Failure];
+        check_invariants(&recloser, &steps).unwrap();
+        recloser.advance(Duration::from_secs(2));
+
+        // Rejected as the only slot is taken by a call in progress
+        let permit = recloser.permit(&epoch::pin());
+        assert!(permit.is_some());
+        check_invariants(&recloser, &[Step::Success]).unwrap();
+        drop(permit);
+
+        // Rejected as the ttl elapsed
+        let steps = [Step::Elapse(Duration::from_secs(6)), Step::Success];
+        check_invariants(&recloser, &steps).unwrap();
+        assert_eq!(CircuitState::Open, recloser.state());
+    }
+
+    #[test]
+    fn slo_windows_elapse() {
+        let slo = Slo::new(0.9).burn_rate(2.0, Duration::from_secs(60), Duration::from_secs(5));
+        let recloser = Recloser::custom()
+            .error_rate(1.0)
+            .closed_len(2)
+            .slo(slo)
+            .build();
+
+        let steps = [Step::Failure, Step::Success, Step::Failure];
+        check_invariants(&recloser, &steps).unwrap();
+        assert_eq!(CircuitState::Open, recloser.state());
+
+        // The previous failures age out of both burn windows
+        recloser.reset();
+        let mut steps = vec![Step::Elapse(Duration::from_secs(120))];
+        steps.extend([Step::Success; 6]);
+        steps.push(Step::Failure);
+        check_invariants(&recloser, &steps).unwrap();
+        assert_eq!(CircuitState::Closed, recloser.state());
+    }
+}
@@ -1,68 +1,238 @@
 use std::future::Future;
+use std::ops::AsyncFnOnce;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crossbeam_epoch as epoch;
-use pin_project::pin_project;
+use pin_project::{pin_project, pinned_drop};
 
-use crate::error::{AnyError, Error, ErrorPredicate};
-use crate::recloser::Recloser;
+use crate::error::{AnyError, Classifier, Error, ErrorPredicate};
+use crate::recloser::{CircuitState, Recloser};
+use crate::wait::{DefaultWait, WaitStrategy};
 
 /// Provides future aware method on top of a regular `Recloser`.
-#[derive(Debug, Clone)]
-pub struct AsyncRecloser {
-    inner: Arc<Recloser>,
+#[derive(Debug)]
+pub struct AsyncRecloser<W = DefaultWait, P = AnyError> {
+    pub(crate) inner: Arc<Recloser<W, P>>,
 }
 
-impl AsyncRecloser {
-    pub fn from(recloser: Recloser) -> Self {
+impl<W, P> Clone for AsyncRecloser<W, P> {
+    fn clone(&self) -> Self {
+        AsyncRecloser {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<W: WaitStrategy, P> AsyncRecloser<W, P> {
+    pub fn from(recloser: Recloser<W, P>) -> Self {
         AsyncRecloser {
             inner: Arc::new(recloser),
         }
     }
 
+    /// Same as `Recloser::state()`.
+    pub fn state(&self) -> CircuitState {
+        self.inner.state()
+    }
+
+    /// Same as `Recloser::trip()`.
+    pub fn trip(&self) {
+        self.inner.trip()
+    }
+
+    /// Same as `Recloser::reset()`.
+    pub fn reset(&self) {
+        self.inner.reset()
+    }
+
+    /// Same as `Recloser::force_open()`.
+    pub fn force_open(&self) {
+        self.inner.force_open()
+    }
+
+    /// Same as `Recloser::force_close()`.
+    pub fn force_close(&self) -> bool {
+        self.inner.force_close()
+    }
+
+    /// Returns a `watch::Receiver` of the current state, so that tasks can await its
+    /// changes rather than polling. Note that the transition from `Open(_)` to
+    /// `HalfOpen(_)` state only happens on the first call made once the open wait
+    /// elapsed, see `Recloser::rejection()` to know when.
+    #[cfg(feature = "tokio")]
+    pub fn state_watch(&self) -> tokio::sync::watch::Receiver<CircuitState> {
+        self.inner.watch.subscribe()
+    }
+
     /// Same as `Recloser::call(...)` but with `Future`.
-    pub fn call<F, T, E>(&self, f: F) -> RecloserFuture<F, AnyError>
+    pub fn call<F, T, E>(&self, f: F) -> RecloserFuture<F, DefaultPredicate<W, P>, W, P>
     where
         F: Future<Output = Result<T, E>>,
+        P: ErrorPredicate<E>,
     {
-        self.call_with(AnyError, f)
+        self.call_with(DefaultPredicate(self.inner.clone()), f)
     }
 
     /// Same as `Recloser::call_with(...)` but with `Future`.
-    pub fn call_with<F, T, E, P>(&self, predicate: P, f: F) -> RecloserFuture<F, P>
+    pub fn call_with<F, T, E, Q>(&self, predicate: Q, f: F) -> RecloserFuture<F, Q, W, P>
     where
         F: Future<Output = Result<T, E>>,
-        P: ErrorPredicate<E>,
+        Q: ErrorPredicate<E>,
     {
-        let recloser = AsyncRecloser {
-            inner: self.inner.clone(),
-        };
-
         RecloserFuture {
-            recloser,
-            future: f,
+            recloser: self.clone(),
+            future: self.inner.monitored(f),
             predicate,
             checked: false,
+            in_flight: false,
+            slot: None,
         }
     }
+
+    /// Same as `call(...)` but with an async closure, that is only invoked when
+    /// the call is permitted.
+    pub async fn call_async<F, T, E>(&self, f: F) -> Result<T, Error<E>>
+    where
+        F: AsyncFnOnce() -> Result<T, E>,
+        P: ErrorPredicate<E>,
+    {
+        self.guarded_call(&self.inner.predicate, f).await
+    }
+
+    /// Same as `call_with(...)` but with an async closure, that is only invoked when
+    /// the call is permitted.
+    pub async fn call_async_with<F, T, E, Q>(&self, predicate: Q, f: F) -> Result<T, Error<E>>
+    where
+        F: AsyncFnOnce() -> Result<T, E>,
+        Q: ErrorPredicate<E>,
+    {
+        self.guarded_call(&predicate, f).await
+    }
+
+    /// Same as `Recloser::call_result_with(...)` but with `Future`.
+    pub async fn call_result_with<C, F, T, E>(&self, failed: C, f: F) -> Result<T, Error<E>>
+    where
+        C: FnOnce(&Result<T, E>) -> bool,
+        F: Future<Output = Result<T, E>>,
+    {
+        let Some(_permit) = self.inner.permit(&epoch::pin()) else {
+            return Err(Error::Rejected);
+        };
+
+        let _in_flight = self.inner.counters.start();
+        let res = self.inner.monitored(f).await;
+        self.inner.on_result(failed(&res), &epoch::pin());
+        res.map_err(Error::Inner)
+    }
+
+    /// Same as `Recloser::call_classified(...)` but with `Future`.
+    pub async fn call_classified<C, F, T, E>(&self, classifier: C, f: F) -> Result<T, Error<E>>
+    where
+        C: Classifier<T, E>,
+        F: Future<Output = Result<T, E>>,
+    {
+        let Some(_permit) = self.inner.permit(&epoch::pin()) else {
+            return Err(Error::Rejected);
+        };
+
+        let _in_flight = self.inner.counters.start();
+        let start = self.inner.now();
+        let res = self.inner.monitored(f).await;
+        let elapsed = Duration::from_nanos(self.inner.now().saturating_sub(start));
+        self.inner.on_timed(
+            classifier.classify_timed(&res, elapsed),
+            elapsed,
+            &epoch::pin(),
+        );
+        res.map_err(Error::Inner)
+    }
+
+    async fn guarded_call<F, T, E, Q>(&self, predicate: &Q, f: F) -> Result<T, Error<E>>
+    where
+        F: AsyncFnOnce() -> Result<T, E>,
+        Q: ErrorPredicate<E>,
+    {
+        let Some(_permit) = self.inner.permit(&epoch::pin()) else {
+            return Err(Error::Rejected);
+        };
+
+        let _in_flight = self.inner.counters.start();
+        match self.inner.monitored(f()).await {
+            Ok(ok) => {
+                self.inner.on_success(&epoch::pin());
+                Ok(ok)
+            }
+            Err(err) => {
+                self.inner.on_err(predicate, &err, &epoch::pin());
+                Err(Error::Inner(err))
+            }
+        }
+    }
+}
+
+/// The default predicate of an `AsyncRecloser`, used by `AsyncRecloser::call(...)`.
+pub struct DefaultPredicate<W = DefaultWait, P = AnyError>(Arc<Recloser<W, P>>);
+
+impl<E, W, P: ErrorPredicate<E>> ErrorPredicate<E> for DefaultPredicate<W, P> {
+    fn is_err(&self, err: &E) -> bool {
+        self.0.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        self.0.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.0.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.0.predicate.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.0.predicate.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.0.predicate.describe(err)
+    }
 }
 
 /// Custom `Future` returned by `AsyncRecloser` wrapped future calls.
-#[pin_project]
-pub struct RecloserFuture<F, P> {
-    recloser: AsyncRecloser,
+#[pin_project(PinnedDrop)]
+pub struct RecloserFuture<F, Q, W = DefaultWait, P = AnyError> {
+    recloser: AsyncRecloser<W, P>,
     #[pin]
-    future: F,
-    predicate: P,
+    future: Monitored<F>,
+    predicate: Q,
     checked: bool,
+    /// Whether the call is counted as in-flight, until it completes or is dropped.
+    in_flight: bool,
+    /// The slot taken in `HalfOpen(_)` state, freed once the call completes or is dropped.
+    slot: Option<u64>,
 }
 
-impl<F, T, E, P> Future for RecloserFuture<F, P>
+#[pinned_drop]
+impl<F, Q, W, P> PinnedDrop for RecloserFuture<F, Q, W, P> {
+    fn drop(self: Pin<&mut Self>) {
+        if self.in_flight {
+            self.recloser.inner.counters.finish();
+        }
+        if let Some(generation) = self.slot {
+            self.recloser.inner.release_slot(generation);
+        }
+    }
+}
+
+impl<F, T, E, Q, W, P> Future for RecloserFuture<F, Q, W, P>
 where
     F: Future<Output = Result<T, E>>,
-    P: ErrorPredicate<E>,
+    Q: ErrorPredicate<E>,
+    W: WaitStrategy,
 {
     type Output = Result<T, Error<E>>;
 
@@ -72,29 +242,66 @@ where
 
         if !&*this.checked {
             *this.checked = true;
-            if !this.recloser.inner.call_permitted(guard) {
+            let Some(permit) = this.recloser.inner.permit(guard) else {
                 return Poll::Ready(Err(Error::Rejected));
-            }
+            };
+            *this.slot = permit.into_slot();
+            *this.in_flight = true;
+            this.recloser.inner.counters.start_detached();
         }
 
-        match this.future.poll(cx) {
+        let poll = this.future.poll(cx);
+        if poll.is_ready() && std::mem::take(this.in_flight) {
+            this.recloser.inner.counters.finish();
+        }
+        if let Some(generation) = this.slot.take_if(|_| poll.is_ready()) {
+            this.recloser.inner.release_slot(generation);
+        }
+        match poll {
             Poll::Ready(Ok(ok)) => {
                 this.recloser.inner.on_success(guard);
                 Poll::Ready(Ok(ok))
             }
             Poll::Pending => Poll::Pending,
             Poll::Ready(Err(err)) => {
-                if this.predicate.is_err(&err) {
-                    this.recloser.inner.on_error(guard);
-                } else {
-                    this.recloser.inner.on_success(guard);
-                }
+                this.recloser.inner.on_err(this.predicate, &err, guard);
                 Poll::Ready(Err(Error::Inner(err)))
             }
         }
     }
 }
 
+impl<W, P> Recloser<W, P> {
+    /// Instruments `future` with the `TaskMonitor` of the `Recloser`, if any.
+    fn monitored<F>(&self, future: F) -> Monitored<F> {
+        #[cfg(feature = "tokio-metrics")]
+        if let Some(monitor) = &self.task_monitor {
+            return Monitored::Instrumented(monitor.instrument(future));
+        }
+        Monitored::Plain(future)
+    }
+}
+
+/// A wrapped future, see `RecloserBuilder::task_monitor(...)`.
+#[pin_project(project = MonitoredProj)]
+enum Monitored<F> {
+    Plain(#[pin] F),
+    #[cfg(feature = "tokio-metrics")]
+    Instrumented(#[pin] tokio_metrics::Instrumented<F>),
+}
+
+impl<F: Future> Future for Monitored<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match self.project() {
+            MonitoredProj::Plain(future) => future.poll(cx),
+            #[cfg(feature = "tokio-metrics")]
+            MonitoredProj::Instrumented(future) => future.poll(cx),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::future;
@@ -116,13 +323,53 @@ mod tests {
         let future = recloser.call(future);
 
         assert!(matches!(task::block_on(future), Err(Error::Inner(()))));
-        assert_eq!(true, recloser.inner.call_permitted(guard));
+        assert!(recloser.inner.call_permitted(guard));
 
         let future = future::ready::<Result<usize, usize>>(Err(12));
         let future = recloser.call(future);
 
         assert!(matches!(task::block_on(future), Err(Error::Inner(12))));
-        assert_eq!(false, recloser.inner.call_permitted(guard));
+        assert!(!recloser.inner.call_permitted(guard));
+    }
+
+    #[test]
+    fn default_predicate() {
+        let guard = &epoch::pin();
+
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .default_predicate(|err: &usize| *err > 10)
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        for err in [1, 1] {
+            let future = recloser.call(future::ready::<Result<(), usize>>(Err(err)));
+            assert!(matches!(task::block_on(future), Err(Error::Inner(_))));
+        }
+        assert!(recloser.inner.call_permitted(guard));
+
+        let future = recloser.call(future::ready::<Result<(), usize>>(Err(12)));
+        assert!(matches!(task::block_on(future), Err(Error::Inner(12))));
+        assert!(!recloser.inner.call_permitted(guard));
+    }
+
+    #[test]
+    fn async_closures() {
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+
+        let mut calls = 0;
+        for _ in 0..3 {
+            let _ = task::block_on(recloser.call_async(async || {
+                calls += 1;
+                Err::<(), usize>(calls)
+            }));
+        }
+
+        // Not invoked once rejected
+        assert_eq!(2, calls);
+
+        let res = task::block_on(recloser.call_async(async || Ok::<(), ()>(())));
+        assert!(matches!(res, Err(Error::Rejected)));
     }
 
     #[test]
@@ -139,7 +386,7 @@ mod tests {
             task::block_on(future),
             Err(Error::Inner(TimeoutError { .. }))
         ));
-        assert_eq!(true, recloser.inner.call_permitted(guard));
+        assert!(recloser.inner.call_permitted(guard));
 
         let future = timeout(Duration::from_millis(5), future::pending::<usize>());
         let future = recloser.call(future);
@@ -148,11 +395,113 @@ mod tests {
             task::block_on(future),
             Err(Error::Inner(TimeoutError { .. }))
         ));
-        assert_eq!(false, recloser.inner.call_permitted(guard));
+        assert!(!recloser.inner.call_permitted(guard));
 
         let future = timeout(Duration::from_millis(5), future::pending::<usize>());
         let future = recloser.call(future);
 
         assert!(matches!(task::block_on(future), Err(Error::Rejected)));
     }
+
+    #[test]
+    fn in_flight() {
+        let recloser = AsyncRecloser::from(Recloser::default());
+        let in_flight = || recloser.inner.metrics().in_flight;
+
+        let future = recloser.call(future::pending::<Result<(), ()>>());
+        let res = task::block_on(timeout(Duration::from_millis(5), future));
+        assert!(res.is_err());
+        assert_eq!(0, in_flight());
+
+        let future = recloser.call(async { Ok::<_, ()>(in_flight()) });
+        assert!(matches!(task::block_on(future), Ok(1)));
+        let res = task::block_on(recloser.call_async(async || Ok::<_, ()>(in_flight())));
+        assert!(matches!(res, Ok(1)));
+        assert_eq!(0, in_flight());
+    }
+
+    #[cfg(feature = "tokio-metrics")]
+    #[test]
+    fn task_monitor() {
+        let monitor = tokio_metrics::TaskMonitor::new();
+        let recloser = Recloser::custom().task_monitor(monitor.clone()).build();
+        let recloser = AsyncRecloser::from(recloser);
+        let in_flight = || {
+            let metrics = monitor.cumulative();
+            metrics.instrumented_count - metrics.dropped_count
+        };
+
+        let future = recloser.call(async { Ok::<_, ()>(in_flight()) });
+        assert!(matches!(task::block_on(future), Ok(1)));
+        let res = task::block_on(recloser.call_async(async || Ok::<_, ()>(in_flight())));
+        assert!(matches!(res, Ok(1)));
+        assert_eq!(0, in_flight());
+        assert_eq!(2, monitor.cumulative().instrumented_count);
+    }
+
+    #[test]
+    fn cancelled_probes() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .half_open_max_calls(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        for _ in 0..2 {
+            let future = recloser.call(future::ready(Err::<(), ()>(())));
+            assert!(matches!(task::block_on(future), Err(Error::Inner(()))));
+        }
+        fake_clock::FakeClock::advance_time(1500);
+
+        // The probe slot is freed once its future is cancelled
+        let probe = recloser.call(future::pending::<Result<(), ()>>());
+        let res = task::block_on(timeout(Duration::from_millis(5), probe));
+        assert!(res.is_err());
+
+        let probe = recloser.call_async(async || future::pending::<Result<(), ()>>().await);
+        let res = task::block_on(timeout(Duration::from_millis(5), probe));
+        assert!(res.is_err());
+
+        let future = recloser.call(future::ready(Ok::<(), ()>(())));
+        assert!(matches!(task::block_on(future), Ok(())));
+    }
+
+    #[test]
+    fn force_open() {
+        let recloser = AsyncRecloser::from(Recloser::default());
+
+        recloser.force_open();
+        let future = recloser.call(future::ready(Ok::<(), ()>(())));
+        assert!(matches!(task::block_on(future), Err(Error::Rejected)));
+
+        assert!(recloser.force_close());
+        assert!(!recloser.force_close());
+        let future = recloser.call(future::ready(Ok::<(), ()>(())));
+        assert!(matches!(task::block_on(future), Ok(())));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn state_watch() {
+        use crate::recloser::CircuitState;
+
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+        let mut watch = recloser.state_watch();
+        assert_eq!(CircuitState::Closed, *watch.borrow());
+
+        let waiter = tokio::spawn(async move {
+            watch
+                .wait_for(|state| *state == CircuitState::Open)
+                .await
+                .is_ok()
+        });
+        for _ in 0..2 {
+            let _ = recloser.call(future::ready(Err::<(), ()>(()))).await;
+        }
+        assert!(waiter.await.unwrap());
+
+        recloser.reset();
+        assert_eq!(CircuitState::Closed, *recloser.state_watch().borrow());
+    }
 }
@@ -0,0 +1,230 @@
+use std::cell::Cell;
+
+use tokio::sync::{broadcast, mpsc};
+
+use crate::error::{AnyError, Error, ErrorPredicate, Outcome};
+use crate::r#async::AsyncRecloser;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// Error returned by guarded senders, that gives back the value when it was not sent.
+#[derive(Debug)]
+pub enum GuardedSendError<T, E> {
+    /// Returned when the underlying sender failed, `E` holds the value.
+    Inner(E),
+    /// Directly returned when in `Open(_)` state, with the value.
+    Rejected(T),
+}
+
+impl<T, E> GuardedSendError<T, E> {
+    fn from_error(err: Error<E>, value: &Cell<Option<T>>) -> Self {
+        match (err, value.take()) {
+            (Error::Inner(err), _) => GuardedSendError::Inner(err),
+            (Error::Rejected, Some(value)) => GuardedSendError::Rejected(value),
+            (Error::Rejected, None) => unreachable!("value sent while rejected"),
+        }
+    }
+}
+
+/// A `tokio::sync::mpsc::Sender` guarded by an `AsyncRecloser`.
+///
+/// Send errors are recorded according to the predicate of the `Recloser`, a full
+/// channel is a failure unless `ignore_full()` is set. Sends are rejected while in
+/// `Open(_)` state and the value is then given back.
+#[derive(Debug)]
+pub struct GuardedSender<T, W = DefaultWait, P = AnyError> {
+    sender: mpsc::Sender<T>,
+    recloser: AsyncRecloser<W, P>,
+    ignore_full: bool,
+}
+
+impl<T, W: WaitStrategy, P> GuardedSender<T, W, P> {
+    pub fn new(sender: mpsc::Sender<T>, recloser: AsyncRecloser<W, P>) -> Self {
+        GuardedSender {
+            sender,
+            recloser,
+            ignore_full: false,
+        }
+    }
+
+    /// Does not record `try_send(...)` to a full channel at all, so that backpressure
+    /// neither helps nor hurts the failure_rate.
+    pub fn ignore_full(mut self) -> Self {
+        self.ignore_full = true;
+        self
+    }
+
+    /// Same as `mpsc::Sender::send(...)`.
+    pub async fn send(&self, value: T) -> Result<(), GuardedSendError<T, mpsc::error::SendError<T>>>
+    where
+        P: ErrorPredicate<mpsc::error::SendError<T>>,
+    {
+        let value = Cell::new(Some(value));
+        self.recloser
+            .call_async(async || self.sender.send(value.take().unwrap()).await)
+            .await
+            .map_err(|err| GuardedSendError::from_error(err, &value))
+    }
+
+    /// Same as `mpsc::Sender::try_send(...)`.
+    pub fn try_send(
+        &self,
+        value: T,
+    ) -> Result<(), GuardedSendError<T, mpsc::error::TrySendError<T>>>
+    where
+        P: ErrorPredicate<mpsc::error::TrySendError<T>>,
+    {
+        let recloser = &self.recloser.inner;
+        let value = Cell::new(Some(value));
+        recloser
+            .guarded(
+                || self.sender.try_send(value.take().unwrap()),
+                |res, guard| match res {
+                    Ok(_) => recloser.on_result(false, guard),
+                    Err(mpsc::error::TrySendError::Full(_)) if self.ignore_full => {
+                        recloser.on_classified(Outcome::Ignore, guard)
+                    }
+                    Err(err) => recloser.on_err(&recloser.predicate, err, guard),
+                },
+            )
+            .map_err(|err| GuardedSendError::from_error(err, &value))
+    }
+
+    /// Returns the underlying `mpsc::Sender`.
+    pub fn inner(&self) -> &mpsc::Sender<T> {
+        &self.sender
+    }
+}
+
+impl<T, W, P> Clone for GuardedSender<T, W, P> {
+    fn clone(&self) -> Self {
+        GuardedSender {
+            sender: self.sender.clone(),
+            recloser: self.recloser.clone(),
+            ignore_full: self.ignore_full,
+        }
+    }
+}
+
+/// A `tokio::sync::broadcast::Sender` guarded by an `AsyncRecloser`.
+///
+/// Sending without any active receiver is recorded according to the predicate of the
+/// `Recloser`, sends are rejected while in `Open(_)` state and the value is then given
+/// back.
+#[derive(Debug)]
+pub struct GuardedBroadcastSender<T, W = DefaultWait, P = AnyError> {
+    sender: broadcast::Sender<T>,
+    recloser: AsyncRecloser<W, P>,
+}
+
+impl<T, W: WaitStrategy, P> GuardedBroadcastSender<T, W, P> {
+    pub fn new(sender: broadcast::Sender<T>, recloser: AsyncRecloser<W, P>) -> Self {
+        GuardedBroadcastSender { sender, recloser }
+    }
+
+    /// Same as `broadcast::Sender::send(...)`, returns the number of receivers
+    /// the value was sent to.
+    pub fn send(
+        &self,
+        value: T,
+    ) -> Result<usize, GuardedSendError<T, broadcast::error::SendError<T>>>
+    where
+        P: ErrorPredicate<broadcast::error::SendError<T>>,
+    {
+        let recloser = &self.recloser.inner;
+        let value = Cell::new(Some(value));
+        recloser
+            .guarded(
+                || self.sender.send(value.take().unwrap()),
+                |res, guard| match res {
+                    Ok(_) => recloser.on_result(false, guard),
+                    Err(err) => recloser.on_err(&recloser.predicate, err, guard),
+                },
+            )
+            .map_err(|err| GuardedSendError::from_error(err, &value))
+    }
+
+    /// Returns the underlying `broadcast::Sender`.
+    pub fn inner(&self) -> &broadcast::Sender<T> {
+        &self.sender
+    }
+}
+
+impl<T, W, P> Clone for GuardedBroadcastSender<T, W, P> {
+    fn clone(&self) -> Self {
+        GuardedBroadcastSender {
+            sender: self.sender.clone(),
+            recloser: self.recloser.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recloser::Recloser;
+
+    #[tokio::test]
+    async fn mpsc_full_and_closed() {
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(2).build());
+        let (tx, mut rx) = mpsc::channel(1);
+        let tx = GuardedSender::new(tx, recloser);
+
+        assert!(matches!(tx.send(1).await, Ok(())));
+        assert!(matches!(
+            tx.try_send(2),
+            Err(GuardedSendError::Inner(mpsc::error::TrySendError::Full(2)))
+        ));
+        assert_eq!(Some(1), rx.recv().await);
+
+        drop(rx);
+        assert!(matches!(tx.send(3).await, Err(GuardedSendError::Inner(_))));
+        assert!(matches!(tx.try_send(4), Err(GuardedSendError::Rejected(4))));
+        assert!(matches!(
+            tx.send(5).await,
+            Err(GuardedSendError::Rejected(5))
+        ));
+    }
+
+    #[test]
+    fn mpsc_backpressure() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .default_predicate(|err: &mpsc::error::TrySendError<usize>| {
+                matches!(err, mpsc::error::TrySendError::Closed(_))
+            })
+            .build();
+        let (tx, _rx) = mpsc::channel(1);
+        let tx = GuardedSender::new(tx, AsyncRecloser::from(recloser));
+
+        // A full channel is not a failure according to the predicate
+        assert!(matches!(tx.try_send(1), Ok(())));
+        for value in 2..4 {
+            assert!(matches!(
+                tx.try_send(value),
+                Err(GuardedSendError::Inner(_))
+            ));
+        }
+        assert_eq!(0, tx.recloser.inner.metrics().failures);
+        assert_eq!(3, tx.recloser.inner.metrics().successes);
+
+        let (tx, _rx) = mpsc::channel(1);
+        let tx = GuardedSender::new(tx, AsyncRecloser::from(Recloser::default())).ignore_full();
+        assert!(matches!(tx.try_send(1), Ok(())));
+        assert!(matches!(tx.try_send(2), Err(GuardedSendError::Inner(_))));
+        assert_eq!(1, tx.recloser.inner.metrics().calls);
+    }
+
+    #[test]
+    fn broadcast_no_receivers() {
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(2).build());
+        let (tx, rx) = broadcast::channel(1);
+        let tx = GuardedBroadcastSender::new(tx, recloser);
+
+        assert!(matches!(tx.send(1), Ok(1)));
+
+        drop(rx);
+        assert!(matches!(tx.send(2), Err(GuardedSendError::Inner(_))));
+        assert!(matches!(tx.send(3), Err(GuardedSendError::Inner(_))));
+        assert!(matches!(tx.send(4), Err(GuardedSendError::Rejected(4))));
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use crate::r#async::AsyncRecloser;
+use crate::recloser::Recloser;
+use crate::wait::WaitStrategy;
+
+/// A command driving a `Recloser` programmatically, see `Recloser::apply(...)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Command {
+    /// Same as `Recloser::trip()`.
+    TripNow,
+    /// Same as `Recloser::reset()`.
+    Reset,
+    /// Same as `Recloser::open_for(...)`, transitions to `HalfOpen(_)` state afterward
+    /// as usual. Use `ForceOpen(Duration::MAX)` for a lasting maintenance.
+    ForceOpen(Duration),
+    /// Updates the settings of the `Recloser` that can be changed at runtime.
+    UpdateConfig(ConfigUpdate),
+}
+
+/// Settings to update at runtime, those left to `None` are kept unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ConfigUpdate {
+    pub error_rate: Option<f32>,
+    /// Picked up the next time `Closed(_)` state is entered, with an empty window.
+    /// Clamped to 1 at least, as by `RecloserBuilder::closed_len(...)`.
+    pub closed_len: Option<usize>,
+    /// Picked up the next time `HalfOpen(_)` state is entered. Clamped to 1 at least,
+    /// as by `RecloserBuilder::half_open_len(...)`.
+    pub half_open_len: Option<usize>,
+    /// Same as `Recloser::set_disabled(...)`.
+    pub disabled: Option<bool>,
+}
+
+impl ConfigUpdate {
+    pub fn new() -> Self {
+        ConfigUpdate::default()
+    }
+
+    pub fn error_rate(mut self, threshold: f32) -> Self {
+        self.error_rate = Some(threshold);
+        self
+    }
+
+    pub fn closed_len(mut self, len: usize) -> Self {
+        self.closed_len = Some(len);
+        self
+    }
+
+    pub fn half_open_len(mut self, len: usize) -> Self {
+        self.half_open_len = Some(len);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = Some(disabled);
+        self
+    }
+}
+
+impl<W: WaitStrategy, P> Recloser<W, P> {
+    /// Applies a `Command`, typically received from a control plane.
+    pub fn apply(&self, command: Command) {
+        match command {
+            Command::TripNow => self.trip(),
+            Command::Reset => self.reset(),
+            Command::ForceOpen(duration) => self.open_for(duration),
+            Command::UpdateConfig(update) => {
+                if let Some(threshold) = update.error_rate {
+                    self.set_threshold(threshold);
+                }
+                self.set_window_lens(update.closed_len, update.half_open_len);
+                if let Some(disabled) = update.disabled {
+                    self.set_disabled(disabled);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W, P> AsyncRecloser<W, P>
+where
+    W: WaitStrategy + Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    /// Spawns a task applying the `Command`s received from `commands`,
+    /// until the channel is closed.
+    pub fn spawn_commands(
+        &self,
+        mut commands: tokio::sync::mpsc::Receiver<Command>,
+    ) -> tokio::task::JoinHandle<()> {
+        let recloser = self.clone();
+        tokio::spawn(async move {
+            while let Some(command) = commands.recv().await {
+                recloser.inner.apply(command);
+            }
+        })
+    }
+
+    /// Same as `spawn_commands(...)` but with a `broadcast` channel, so that a single
+    /// control plane can drive many `Recloser`s. Lagging behind skips the missed
+    /// `Command`s.
+    pub fn spawn_broadcast_commands(
+        &self,
+        mut commands: tokio::sync::broadcast::Receiver<Command>,
+    ) -> tokio::task::JoinHandle<()> {
+        use tokio::sync::broadcast::error::RecvError;
+
+        let recloser = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match commands.recv().await {
+                    Ok(command) => recloser.inner.apply(command),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_clock::FakeClock;
+
+    use super::*;
+    use crate::error::Error;
+    use crate::recloser::CircuitState;
+
+    #[test]
+    fn apply_commands() {
+        let recl = Recloser::custom()
+            .closed_len(4)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        recl.apply(Command::TripNow);
+        assert_eq!(CircuitState::Open, recl.state());
+        recl.apply(Command::Reset);
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        recl.apply(Command::ForceOpen(Duration::from_secs(10)));
+        FakeClock::advance_time(5_000);
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+        FakeClock::advance_time(6_000);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+
+        // Trips on a single failure out of 4 calls
+        recl.apply(Command::Reset);
+        recl.apply(Command::UpdateConfig(ConfigUpdate::new().error_rate(0.25)));
+        for _ in 0..4 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
+
+        // Windows are resized once their state is entered
+        recl.apply(Command::UpdateConfig(
+            ConfigUpdate::new().closed_len(1).half_open_len(1),
+        ));
+        assert_eq!((4, 10), recl.windows());
+        recl.apply(Command::Reset);
+        assert_eq!((1, 10), recl.windows());
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, recl.state());
+        FakeClock::advance_time(1_500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!((1, 1), recl.windows());
+    }
+
+    #[test]
+    fn zero_lens() {
+        let recl = Recloser::custom().closed_len(0).half_open_len(0).build();
+        assert_eq!((1, 1), recl.windows());
+
+        let recl = Recloser::custom()
+            .closed_len(4)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        recl.apply(Command::UpdateConfig(
+            ConfigUpdate::new().closed_len(0).half_open_len(0),
+        ));
+        recl.apply(Command::Reset);
+        assert_eq!((1, 10), recl.windows());
+
+        // Successes never trip the breaker
+        for _ in 0..3 {
+            assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        recl.apply(Command::TripNow);
+        FakeClock::advance_time(1_500);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!((1, 1), recl.windows());
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn command_channels() {
+        let recloser = AsyncRecloser::from(Recloser::default());
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let handle = recloser.spawn_commands(rx);
+        tx.send(Command::TripNow).await.unwrap();
+        drop(tx);
+        handle.await.unwrap();
+        assert_eq!(CircuitState::Open, recloser.state());
+
+        let (tx, rx) = tokio::sync::broadcast::channel(1);
+        let handle = recloser.spawn_broadcast_commands(rx);
+        tx.send(Command::Reset).unwrap();
+        drop(tx);
+        handle.await.unwrap();
+        assert_eq!(CircuitState::Closed, recloser.state());
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use crate::recloser::{Recloser, RecloserBuilder};
+
+/// The plain settings of a `Recloser`, as data rather than builder calls, for instance
+/// to generate configurations or to compare them.
+///
+/// Its `Default` is the same as `Recloser::default()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct RecloserConfig {
+    pub error_rate: f32,
+    pub closed_len: usize,
+    pub half_open_len: usize,
+    pub half_open_min_calls: Option<usize>,
+    pub half_open_max_calls: Option<usize>,
+    pub half_open_ttl: Option<Duration>,
+    pub open_wait: Duration,
+}
+
+impl Default for RecloserConfig {
+    fn default() -> Self {
+        RecloserConfig {
+            error_rate: 0.5,
+            closed_len: 100,
+            half_open_len: 10,
+            half_open_min_calls: None,
+            half_open_max_calls: None,
+            half_open_ttl: None,
+            open_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RecloserConfig {
+    pub fn new() -> Self {
+        RecloserConfig::default()
+    }
+
+    /// Returns a `RecloserBuilder` with these settings, to be customized further.
+    pub fn builder(&self) -> RecloserBuilder {
+        let mut builder = Recloser::custom()
+            .error_rate(self.error_rate)
+            .closed_len(self.closed_len)
+            .half_open_len(self.half_open_len)
+            .open_wait(self.open_wait);
+        if let Some(min_calls) = self.half_open_min_calls {
+            builder = builder.half_open_min_calls(min_calls);
+        }
+        if let Some(max_calls) = self.half_open_max_calls {
+            builder = builder.half_open_max_calls(max_calls);
+        }
+        match self.half_open_ttl {
+            Some(ttl) => builder.half_open_ttl(ttl),
+            None => builder,
+        }
+    }
+}
+
+impl From<RecloserConfig> for RecloserBuilder {
+    fn from(config: RecloserConfig) -> Self {
+        config.builder()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recloser::CircuitState;
+
+    #[test]
+    fn config_builder() {
+        let config = RecloserConfig {
+            closed_len: 1,
+            ..RecloserConfig::default()
+        };
+        let recloser = RecloserBuilder::from(config).build();
+
+        for _ in 0..2 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, recloser.state());
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            recloser.rejection().and_then(|r| r.retry_after)
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::recloser::{CircuitState, Recloser};
+use crate::wait::WaitStrategy;
+
+const STATES: [CircuitState; 4] = [
+    CircuitState::Closed,
+    CircuitState::Open,
+    CircuitState::HalfOpen,
+    CircuitState::ForcedOpen,
+];
+
+/// A transition of the state machine, along with how many times it happened.
+struct Edge {
+    from: CircuitState,
+    to: CircuitState,
+    label: String,
+    count: u64,
+}
+
+impl<W: WaitStrategy, P> Recloser<W, P> {
+    /// Returns a Graphviz diagram of the state machine, annotated with the current state,
+    /// the thresholds and the number of times each transition happened.
+    pub fn to_dot(&self) -> String {
+        let current = self.state();
+        let mut dot = String::from("digraph recloser {\n    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=rounded];\n");
+        for state in STATES {
+            if state == current {
+                let _ = writeln!(
+                    dot,
+                    "    {state:?} [style=\"rounded,filled\", fillcolor=lightblue];"
+                );
+            } else {
+                let _ = writeln!(dot, "    {state:?};");
+            }
+        }
+        for edge in self.edges() {
+            let _ = writeln!(
+                dot,
+                "    {:?} -> {:?} [label=\"{} ({})\"];",
+                edge.from, edge.to, edge.label, edge.count
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Same as `to_dot()`, but as a Mermaid state diagram.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("stateDiagram-v2\n    [*] --> Closed\n");
+        for edge in self.edges() {
+            let _ = writeln!(
+                mermaid,
+                "    {:?} --> {:?}: {} ({})",
+                edge.from, edge.to, edge.label, edge.count
+            );
+        }
+        mermaid.push_str("    classDef current fill:lightblue\n");
+        let _ = writeln!(mermaid, "    class {:?} current", self.state());
+        mermaid
+    }
+
+    fn edges(&self) -> Vec<Edge> {
+        use CircuitState::*;
+
+        let (closed_len, half_open_len) = self.windows();
+        let mut edges = vec![
+            (
+                Closed,
+                Open,
+                format!(
+                    "failure_rate >= {} over {} calls",
+                    self.threshold(),
+                    closed_len
+                ),
+            ),
+            (Open, HalfOpen, format!("after {:?}", self.current_wait())),
+            (
+                HalfOpen,
+                Closed,
+                format!(
+                    "failure_rate < {} over {} calls",
+                    self.half_open_threshold(),
+                    half_open_len
+                ),
+            ),
+            (
+                HalfOpen,
+                Open,
+                format!("failure_rate >= {}", self.half_open_threshold()),
+            ),
+            (ForcedOpen, Closed, String::from("force_close()")),
+        ]
+        .into_iter()
+        .map(|(from, to, label)| Edge {
+            count: self.transitions(from, to),
+            from,
+            to,
+            label,
+        })
+        .collect::<Vec<_>>();
+
+        // Other transitions, such as manual ones, are only shown once they happened
+        for from in STATES {
+            for to in STATES {
+                let count = self.transitions(from, to);
+                let known = edges.iter().any(|e| e.from == from && e.to == to);
+                if count > 0 && !known {
+                    let label = String::from("manual");
+                    edges.push(Edge {
+                        from,
+                        to,
+                        label,
+                        count,
+                    });
+                }
+            }
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn diagrams() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        recl.force_open();
+
+        let dot = recl.to_dot();
+        assert!(dot.starts_with("digraph recloser {"));
+        assert!(dot.contains("ForcedOpen [style=\"rounded,filled\", fillcolor=lightblue];"));
+        assert!(dot.contains("Closed -> Open [label=\"failure_rate >= 0.5 over 1 calls (1)\"];"));
+        assert!(dot.contains("Open -> HalfOpen [label=\"after 1s (0)\"];"));
+        assert!(dot.contains("Open -> ForcedOpen [label=\"manual (1)\"];"));
+
+        let mermaid = recl.to_mermaid();
+        assert!(mermaid.starts_with("stateDiagram-v2\n    [*] --> Closed\n"));
+        assert!(mermaid.contains("    Closed --> Open: failure_rate >= 0.5 over 1 calls (1)\n"));
+        assert!(mermaid.contains("    class ForcedOpen current\n"));
+    }
+}
@@ -1,5 +1,16 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
 /// Error returned by `Recloser` wrapped function calls.
+///
+/// With the `serde` feature, it serializes as `{"kind": "inner", "error": ...}`
+/// or `{"kind": "rejected"}`.
 #[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", content = "error", rename_all = "snake_case")
+)]
 pub enum Error<E> {
     /// Returned when got an `Err(e)` while performing a wrapped function call
     /// in `Closed(_)` or `HalfOpen(_)` state.
@@ -11,6 +22,320 @@ pub enum Error<E> {
 /// A trait used to determine whether an `E` should be considered as a failure.
 pub trait ErrorPredicate<E> {
     fn is_err(&self, err: &E) -> bool;
+
+    /// Whether `err` is a critical failure that won't heal within a normal open wait,
+    /// such as revoked credentials. It then opens the `Recloser` for the duration set by
+    /// `RecloserBuilder::fuse_wait(...)`, regardless of the failure_rate.
+    fn is_fuse(&self, _err: &E) -> bool {
+        false
+    }
+
+    /// Whether `err` is not recorded at all, so that it neither helps nor hurts the
+    /// failure_rate, unlike the errors not considered as failures by `is_err(...)`.
+    fn is_ignored(&self, _err: &E) -> bool {
+        false
+    }
+
+    /// How many failures `err` counts as in the failure_rate, `1.0` by default.
+    fn weight(&self, _err: &E) -> f32 {
+        1.0
+    }
+
+    /// The category of `err`, that may trip the `Recloser` at its own threshold, see
+    /// `RecloserBuilder::category(...)`. Errors are not categorized by default.
+    fn category(&self, _err: &E) -> Option<&'static str> {
+        None
+    }
+
+    /// A summary of `err` when it tripped the `Recloser`, to be retrieved with
+    /// `Recloser::trip_error()`. Nothing is kept by default.
+    fn describe(&self, _err: &E) -> Option<String> {
+        None
+    }
+
+    /// Also considers the errors accepted by `fuse` as critical failures, see `is_fuse(...)`.
+    fn fuse_on<F>(self, fuse: F) -> FuseOn<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> bool,
+    {
+        FuseOn {
+            predicate: self,
+            fuse,
+        }
+    }
+
+    /// Does not record the errors accepted by `ignore`, see `is_ignored(...)`.
+    fn ignore_on<F>(self, ignore: F) -> IgnoreOn<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> bool,
+    {
+        IgnoreOn {
+            predicate: self,
+            ignore,
+        }
+    }
+
+    /// Weighs the failures as returned by `f`, see `weight(...)`.
+    fn weighted<F>(self, f: F) -> Weighted<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> f32,
+    {
+        Weighted {
+            predicate: self,
+            weight: f,
+        }
+    }
+
+    /// Categorizes the errors as returned by `f`, see `category(...)`.
+    fn categorize<F>(self, f: F) -> Categorize<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> Option<&'static str>,
+    {
+        Categorize {
+            predicate: self,
+            category: f,
+        }
+    }
+
+    /// Keeps the summary returned by `f` of the errors that trip the `Recloser`, see
+    /// `describe(...)`.
+    fn describe_with<F>(self, f: F) -> DescribeWith<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&E) -> String,
+    {
+        DescribeWith {
+            predicate: self,
+            describe: f,
+        }
+    }
+}
+
+/// An `ErrorPredicate` that also blows the fuse on some errors, see
+/// `ErrorPredicate::fuse_on(...)`.
+#[derive(Debug, Clone, Copy)]
+pub struct FuseOn<P, F> {
+    predicate: P,
+    fuse: F,
+}
+
+impl<E, P, F> ErrorPredicate<E> for FuseOn<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&E) -> bool,
+{
+    fn is_err(&self, err: &E) -> bool {
+        (self.fuse)(err) || self.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        (self.fuse)(err) || self.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.predicate.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.predicate.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.predicate.describe(err)
+    }
+}
+
+/// An `ErrorPredicate` that weighs its failures, see `ErrorPredicate::weighted(...)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Weighted<P, F> {
+    predicate: P,
+    weight: F,
+}
+
+impl<E, P, F> ErrorPredicate<E> for Weighted<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&E) -> f32,
+{
+    fn is_err(&self, err: &E) -> bool {
+        self.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        self.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        (self.weight)(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.predicate.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.predicate.describe(err)
+    }
+}
+
+/// An `ErrorPredicate` that categorizes its errors, see `ErrorPredicate::categorize(...)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Categorize<P, F> {
+    predicate: P,
+    category: F,
+}
+
+impl<E, P, F> ErrorPredicate<E> for Categorize<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&E) -> Option<&'static str>,
+{
+    fn is_err(&self, err: &E) -> bool {
+        self.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        self.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.predicate.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        (self.category)(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.predicate.describe(err)
+    }
+}
+
+/// An `ErrorPredicate` that does not record some errors, see
+/// `ErrorPredicate::ignore_on(...)`.
+#[derive(Debug, Clone, Copy)]
+pub struct IgnoreOn<P, F> {
+    predicate: P,
+    ignore: F,
+}
+
+impl<E, P, F> ErrorPredicate<E> for IgnoreOn<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&E) -> bool,
+{
+    fn is_err(&self, err: &E) -> bool {
+        !(self.ignore)(err) && self.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        !(self.ignore)(err) && self.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        (self.ignore)(err) || self.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.predicate.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.predicate.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.predicate.describe(err)
+    }
+}
+
+/// An `ErrorPredicate` that does not record the errors that are not failures, rather
+/// than recording them as successes. For instance `IgnoreOthers(Timeouts)` only records
+/// timeouts among errors.
+#[derive(Debug, Clone, Copy)]
+pub struct IgnoreOthers<P>(pub P);
+
+impl<E, P> ErrorPredicate<E> for IgnoreOthers<P>
+where
+    P: ErrorPredicate<E>,
+{
+    fn is_err(&self, err: &E) -> bool {
+        self.0.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        self.0.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.0.is_ignored(err) || !(self.0.is_err(err) || self.0.is_fuse(err))
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.0.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.0.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        self.0.describe(err)
+    }
+}
+
+/// An `ErrorPredicate` that describes the errors tripping the `Recloser`, see
+/// `ErrorPredicate::describe_with(...)`.
+#[derive(Debug, Clone, Copy)]
+pub struct DescribeWith<P, F> {
+    predicate: P,
+    describe: F,
+}
+
+impl<E, P, F> ErrorPredicate<E> for DescribeWith<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&E) -> String,
+{
+    fn is_err(&self, err: &E) -> bool {
+        self.predicate.is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        self.predicate.is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        self.predicate.is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        self.predicate.weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        self.predicate.category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        Some((self.describe)(err))
+    }
 }
 
 impl<F, E> ErrorPredicate<E> for F
@@ -22,6 +347,171 @@ where
     }
 }
 
+/// How the result of a call is recorded, see `Classifier`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Failure,
+    /// The call is not recorded at all, neither as a success nor as a failure.
+    Ignore,
+    /// Opens the `Recloser` immediately regardless of the failure_rate, for the usual
+    /// open wait rather than the `fuse_wait(...)` of errors accepted by `fuse_on(...)`.
+    Fatal,
+}
+
+/// A trait used to classify the whole result of a call, see `Recloser::call_classified(...)`.
+pub trait Classifier<T, E> {
+    fn classify(&self, res: &Result<T, E>) -> Outcome;
+
+    /// Same as `classify(...)`, with the time the call took. Ignores it by default.
+    fn classify_timed(&self, res: &Result<T, E>, _elapsed: Duration) -> Outcome {
+        self.classify(res)
+    }
+}
+
+impl<F, T, E> Classifier<T, E> for F
+where
+    F: Fn(&Result<T, E>) -> Outcome,
+{
+    fn classify(&self, res: &Result<T, E>) -> Outcome {
+        self(res)
+    }
+}
+
+/// A `Classifier` that also considers the calls slower than `limit` as failures, even if
+/// they succeeded.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowerThan<C> {
+    classifier: C,
+    limit: Duration,
+}
+
+impl<C> SlowerThan<C> {
+    pub fn new(classifier: C, limit: Duration) -> Self {
+        SlowerThan { classifier, limit }
+    }
+}
+
+impl<T, E, C: Classifier<T, E>> Classifier<T, E> for SlowerThan<C> {
+    fn classify(&self, res: &Result<T, E>) -> Outcome {
+        self.classifier.classify(res)
+    }
+
+    fn classify_timed(&self, res: &Result<T, E>, elapsed: Duration) -> Outcome {
+        match self.classifier.classify_timed(res, elapsed) {
+            Outcome::Success if elapsed > self.limit => Outcome::Failure,
+            class => class,
+        }
+    }
+}
+
+/// A `Classifier` that also considers some `Ok(_)` values as failures, such as responses
+/// with a 5xx status, and otherwise relies on an `ErrorPredicate`.
+#[derive(Debug, Clone, Copy)]
+pub struct FailOnOk<P, F> {
+    predicate: P,
+    fail: F,
+}
+
+impl<P, F> FailOnOk<P, F> {
+    /// Considers the `Ok(_)` values accepted by `fail` as failures, and the `Err(_)`s as
+    /// classified by `predicate`.
+    pub fn new(predicate: P, fail: F) -> Self {
+        FailOnOk { predicate, fail }
+    }
+}
+
+impl<T, E, P, F> Classifier<T, E> for FailOnOk<P, F>
+where
+    P: ErrorPredicate<E>,
+    F: Fn(&T) -> bool,
+{
+    fn classify(&self, res: &Result<T, E>) -> Outcome {
+        match res {
+            Ok(ok) if (self.fail)(ok) => Outcome::Failure,
+            Ok(_) => Outcome::Success,
+            Err(err) if self.predicate.is_ignored(err) => Outcome::Ignore,
+            Err(err) if self.predicate.is_fuse(err) => Outcome::Fatal,
+            Err(err) if self.predicate.is_err(err) => Outcome::Failure,
+            Err(_) => Outcome::Success,
+        }
+    }
+}
+
+/// Shares a predicate between many call sites without cloning it.
+impl<E, P: ErrorPredicate<E> + ?Sized> ErrorPredicate<E> for std::sync::Arc<P> {
+    fn is_err(&self, err: &E) -> bool {
+        (**self).is_err(err)
+    }
+
+    fn is_fuse(&self, err: &E) -> bool {
+        (**self).is_fuse(err)
+    }
+
+    fn is_ignored(&self, err: &E) -> bool {
+        (**self).is_ignored(err)
+    }
+
+    fn weight(&self, err: &E) -> f32 {
+        (**self).weight(err)
+    }
+
+    fn category(&self, err: &E) -> Option<&'static str> {
+        (**self).category(err)
+    }
+
+    fn describe(&self, err: &E) -> Option<String> {
+        (**self).describe(err)
+    }
+}
+
+/// Implements `ErrorPredicate` for boxed and borrowed trait objects.
+///
+/// Generic `Box<P>` and `&P` cannot implement it, as they would conflict with the
+/// implementation for closures: both implement `Fn(&E) -> bool` when `P` does. Boxed or
+/// borrowed concrete predicates have to be turned into a `dyn ErrorPredicate<E>`, or
+/// shared behind an `Arc`.
+macro_rules! dyn_predicate {
+    ($($ty:ty),+) => {
+        $(
+            impl<E> ErrorPredicate<E> for $ty {
+                fn is_err(&self, err: &E) -> bool {
+                    (**self).is_err(err)
+                }
+
+                fn is_fuse(&self, err: &E) -> bool {
+                    (**self).is_fuse(err)
+                }
+
+                fn is_ignored(&self, err: &E) -> bool {
+                    (**self).is_ignored(err)
+                }
+
+                fn weight(&self, err: &E) -> f32 {
+                    (**self).weight(err)
+                }
+
+                fn category(&self, err: &E) -> Option<&'static str> {
+                    (**self).category(err)
+                }
+
+                fn describe(&self, err: &E) -> Option<String> {
+                    (**self).describe(err)
+                }
+            }
+        )+
+    };
+}
+
+dyn_predicate!(
+    Box<dyn ErrorPredicate<E>>,
+    Box<dyn ErrorPredicate<E> + Send>,
+    Box<dyn ErrorPredicate<E> + Send + Sync>,
+    &dyn ErrorPredicate<E>,
+    &(dyn ErrorPredicate<E> + Send),
+    &(dyn ErrorPredicate<E> + Send + Sync)
+);
+
 /// Considers any value as a failure.
 #[derive(Debug)]
 pub struct AnyError;
@@ -31,3 +521,483 @@ impl<E> ErrorPredicate<E> for AnyError {
         true
     }
 }
+
+/// Only considers type erased errors whose `source()` chain, including themselves,
+/// contains a `T` accepted by the filter as failures. Most errors arrive wrapped, for
+/// instance an `io::Error` behind a `hyper::Error` in a `Box<dyn Error + Send + Sync>`,
+/// an `anyhow::Error` or an `eyre::Report`.
+///
+/// ```rust
+/// use std::io;
+/// use recloser::{CausedBy, ErrorPredicate};
+///
+/// let refused = CausedBy::filter(|e: &io::Error| e.kind() == io::ErrorKind::ConnectionRefused);
+/// let err: Box<dyn std::error::Error + Send + Sync> = "not found".into();
+/// assert!(!refused.is_err(&err));
+///
+/// let err: Box<dyn std::error::Error + Send + Sync> = io::Error::other("boom").into();
+/// assert!(CausedBy::<io::Error>::new().is_err(&err));
+/// ```
+pub struct CausedBy<T, F = fn(&T) -> bool> {
+    filter: F,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T> CausedBy<T> {
+    /// Accepts any `T` found in the chain.
+    pub fn new() -> Self {
+        CausedBy::filter(|_| true)
+    }
+}
+
+impl<T> Default for CausedBy<T> {
+    fn default() -> Self {
+        CausedBy::new()
+    }
+}
+
+impl<T, F: Fn(&T) -> bool> CausedBy<T, F> {
+    /// Only accepts the `T`s found in the chain for which `filter` returns `true`.
+    pub fn filter(filter: F) -> Self {
+        CausedBy {
+            filter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, F> CausedBy<T, F>
+where
+    T: std::error::Error + 'static,
+    F: Fn(&T) -> bool,
+{
+    fn is_caused(&self, err: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if err.downcast_ref::<T>().is_some_and(&self.filter) {
+                return true;
+            }
+            source = err.source();
+        }
+        false
+    }
+}
+
+impl<E, T, F> ErrorPredicate<E> for CausedBy<T, F>
+where
+    E: AsRef<dyn std::error::Error + Send + Sync>,
+    T: std::error::Error + 'static,
+    F: Fn(&T) -> bool,
+{
+    fn is_err(&self, err: &E) -> bool {
+        self.is_caused(err.as_ref())
+    }
+}
+
+impl<T, F> std::fmt::Debug for CausedBy<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CausedBy<{}>", std::any::type_name::<T>())
+    }
+}
+
+/// Only considers timeouts as failures: `io::ErrorKind::TimedOut` errors, as well as
+/// `tokio::time::error::Elapsed` and `async_std::future::TimeoutError` with the `tokio`
+/// and `async-std` features, and `RpcError::DeadlineExceeded` with the `tarpc` feature.
+/// Boxed errors are considered as timeouts when any error of their chain is.
+///
+/// Other timers are not recognized, for instance `futures-timer` only provides a `Delay`
+/// without a timeout error type: timeouts built on it should be reported as
+/// `io::ErrorKind::TimedOut` errors, or be matched by a dedicated `ErrorPredicate`.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeouts;
+
+impl Timeouts {
+    fn is_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            #[cfg(feature = "tokio")]
+            if err.is::<tokio::time::error::Elapsed>() {
+                return true;
+            }
+            #[cfg(feature = "async-std")]
+            if err.is::<async_std::future::TimeoutError>() {
+                return true;
+            }
+            if matches!(err.downcast_ref::<std::io::Error>(), Some(e) if e.kind() == std::io::ErrorKind::TimedOut)
+            {
+                return true;
+            }
+            source = err.source();
+        }
+        false
+    }
+}
+
+impl ErrorPredicate<std::io::Error> for Timeouts {
+    fn is_err(&self, err: &std::io::Error) -> bool {
+        err.kind() == std::io::ErrorKind::TimedOut
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl ErrorPredicate<tokio::time::error::Elapsed> for Timeouts {
+    fn is_err(&self, _err: &tokio::time::error::Elapsed) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl ErrorPredicate<async_std::future::TimeoutError> for Timeouts {
+    fn is_err(&self, _err: &async_std::future::TimeoutError) -> bool {
+        true
+    }
+}
+
+impl ErrorPredicate<Box<dyn std::error::Error + Send + Sync>> for Timeouts {
+    fn is_err(&self, err: &Box<dyn std::error::Error + Send + Sync>) -> bool {
+        Timeouts::is_timeout(err.as_ref())
+    }
+}
+
+/// Only considers the `io::Error`s of the given kinds as failures. Boxed errors are
+/// considered as failures when any error of their chain is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorKinds(Vec<std::io::ErrorKind>);
+
+impl ErrorKinds {
+    pub fn new(kinds: impl IntoIterator<Item = std::io::ErrorKind>) -> Self {
+        ErrorKinds(kinds.into_iter().collect())
+    }
+
+    /// The kinds usually telling that the peer is unhealthy or unreachable:
+    /// `ConnectionRefused`, `ConnectionReset`, `ConnectionAborted`, `NotConnected`,
+    /// `BrokenPipe`, `TimedOut` and `HostUnreachable`.
+    pub fn network() -> Self {
+        use std::io::ErrorKind::*;
+        ErrorKinds::new([
+            ConnectionRefused,
+            ConnectionReset,
+            ConnectionAborted,
+            NotConnected,
+            BrokenPipe,
+            TimedOut,
+            HostUnreachable,
+        ])
+    }
+
+    fn contains(&self, err: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if matches!(err.downcast_ref::<std::io::Error>(), Some(e) if self.0.contains(&e.kind()))
+            {
+                return true;
+            }
+            source = err.source();
+        }
+        false
+    }
+}
+
+impl Default for ErrorKinds {
+    fn default() -> Self {
+        ErrorKinds::network()
+    }
+}
+
+impl ErrorPredicate<std::io::Error> for ErrorKinds {
+    fn is_err(&self, err: &std::io::Error) -> bool {
+        self.0.contains(&err.kind())
+    }
+}
+
+impl ErrorPredicate<Box<dyn std::error::Error + Send + Sync>> for ErrorKinds {
+    fn is_err(&self, err: &Box<dyn std::error::Error + Send + Sync>) -> bool {
+        self.contains(err.as_ref())
+    }
+}
+
+/// An `ErrorPredicate` for `sqlx::Error`, that only considers the errors telling that the
+/// database is unhealthy or unreachable as failures: I/O, TLS, protocol and pool errors.
+///
+/// Errors caused by the application rather than the database, such as `RowNotFound` or
+/// constraint violations, are ignored. Other errors, such as decoding ones, are not
+/// failures.
+#[cfg(feature = "sqlx")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SqlxErrors;
+
+#[cfg(feature = "sqlx")]
+impl ErrorPredicate<sqlx::Error> for SqlxErrors {
+    fn is_err(&self, err: &sqlx::Error) -> bool {
+        matches!(
+            err,
+            sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::Protocol(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::WorkerCrashed
+        )
+    }
+
+    fn is_ignored(&self, err: &sqlx::Error) -> bool {
+        match err {
+            sqlx::Error::RowNotFound => true,
+            sqlx::Error::Database(err) => err.kind() != sqlx::error::ErrorKind::Other,
+            _ => false,
+        }
+    }
+}
+
+/// An `ErrorPredicate` for `redis::RedisError`, that only considers the errors telling
+/// that the server is unhealthy or unreachable as failures: I/O and cluster errors, or a
+/// server busy loading its dataset or missing its master.
+///
+/// Type and parse errors, caused by the application rather than the server, are ignored.
+/// Other errors are not failures.
+#[cfg(feature = "redis")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedisErrors;
+
+#[cfg(feature = "redis")]
+impl ErrorPredicate<redis::RedisError> for RedisErrors {
+    fn is_err(&self, err: &redis::RedisError) -> bool {
+        use redis::ErrorKind::*;
+        err.is_io_error()
+            || matches!(
+                err.kind(),
+                ClusterDown | TryAgain | MasterDown | BusyLoadingError | ClusterConnectionNotFound
+            )
+    }
+
+    fn is_ignored(&self, err: &redis::RedisError) -> bool {
+        matches!(
+            err.kind(),
+            redis::ErrorKind::TypeError | redis::ErrorKind::ParseError
+        )
+    }
+}
+
+/// The error a `Error::Rejected` is converted into when boxed as a `tower::BoxError`,
+/// or as the source of a `tarpc` `RpcError::Send(_)`.
+#[cfg(any(feature = "tower", feature = "tarpc"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedError;
+
+#[cfg(any(feature = "tower", feature = "tarpc"))]
+impl std::fmt::Display for RejectedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("call rejected by recloser")
+    }
+}
+
+#[cfg(any(feature = "tower", feature = "tarpc"))]
+impl std::error::Error for RejectedError {}
+
+#[cfg(feature = "tower")]
+impl<E> From<Error<E>> for tower::BoxError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn from(err: Error<E>) -> Self {
+        match err {
+            Error::Inner(err) => Box::new(err),
+            Error::Rejected => Box::new(RejectedError),
+        }
+    }
+}
+
+#[cfg(feature = "tower")]
+impl Error<std::convert::Infallible> {
+    /// Returns whether a `RejectedError` is found in the chain of a `tower::BoxError`,
+    /// that is whether the error comes from a call rejected by a `Recloser`.
+    pub fn is_rejection(err: &tower::BoxError) -> bool {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err.as_ref());
+        while let Some(err) = source {
+            if err.is::<RejectedError>() {
+                return true;
+            }
+            source = err.source();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Wrapper(Box<dyn std::error::Error + Send + Sync>);
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "wrapped: {}", self.0)
+        }
+    }
+
+    impl std::error::Error for Wrapper {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(self.0.as_ref())
+        }
+    }
+
+    #[test]
+    fn timeouts() {
+        let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+        assert!(Timeouts.is_err(&timed_out));
+        assert!(!Timeouts.is_err(&io::Error::from(io::ErrorKind::NotFound)));
+
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapper(timed_out.into()));
+        assert!(Timeouts.is_err(&err));
+        let err: Box<dyn std::error::Error + Send + Sync> = "other".into();
+        assert!(!Timeouts.is_err(&err));
+    }
+
+    #[test]
+    fn caused_by() {
+        let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapper(timed_out.into()));
+        assert!(CausedBy::<io::Error>::new().is_err(&err));
+        assert!(!CausedBy::<fmt::Error>::new().is_err(&err));
+
+        let refused =
+            CausedBy::filter(|e: &io::Error| e.kind() == io::ErrorKind::ConnectionRefused);
+        assert!(!refused.is_err(&err));
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapper(
+            io::Error::from(io::ErrorKind::ConnectionRefused).into(),
+        ));
+        assert!(refused.is_err(&err));
+    }
+
+    #[test]
+    fn error_kinds() {
+        let refused = io::Error::from(io::ErrorKind::ConnectionRefused);
+        assert!(ErrorKinds::network().is_err(&refused));
+        assert!(!ErrorKinds::new([io::ErrorKind::TimedOut]).is_err(&refused));
+
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapper(refused.into()));
+        assert!(ErrorKinds::default().is_err(&err));
+        let err: Box<dyn std::error::Error + Send + Sync> = "other".into();
+        assert!(!ErrorKinds::default().is_err(&err));
+    }
+
+    #[test]
+    fn ignore_on() {
+        let predicate = AnyError
+            .fuse_on(|status: &u16| *status == 401)
+            .ignore_on(|status: &u16| *status == 401 || *status == 404);
+        assert!(predicate.is_err(&500));
+        assert!(!predicate.is_err(&404));
+        assert!(!predicate.is_fuse(&401));
+        assert!(predicate.is_ignored(&401));
+        assert!(!predicate.is_ignored(&500));
+    }
+
+    #[test]
+    fn shared_predicates() {
+        let timeouts = std::sync::Arc::new(
+            Timeouts.fuse_on(|e: &io::Error| e.kind() == io::ErrorKind::PermissionDenied),
+        );
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(timeouts.clone().is_fuse(&denied));
+
+        let boxed: Box<dyn ErrorPredicate<io::Error> + Send + Sync> = Box::new(timeouts);
+        assert!(boxed.is_err(&io::Error::from(io::ErrorKind::TimedOut)));
+        let borrowed: &(dyn ErrorPredicate<io::Error> + Send + Sync) = &Timeouts;
+        assert!(!borrowed.is_err(&denied));
+        let local: Box<dyn ErrorPredicate<io::Error>> = Box::new(Timeouts);
+        assert!(!(&*local as &dyn ErrorPredicate<io::Error>).is_err(&denied));
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn sqlx_errors() {
+        assert!(SqlxErrors.is_err(&sqlx::Error::PoolTimedOut));
+        assert!(SqlxErrors.is_err(&sqlx::Error::Io(io::ErrorKind::ConnectionReset.into())));
+        assert!(SqlxErrors.is_ignored(&sqlx::Error::RowNotFound));
+
+        let decode = sqlx::Error::ColumnNotFound("id".into());
+        assert!(!SqlxErrors.is_err(&decode));
+        assert!(!SqlxErrors.is_ignored(&decode));
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn redis_errors() {
+        use redis::{ErrorKind, RedisError};
+
+        let refused = RedisError::from(io::Error::from(io::ErrorKind::ConnectionRefused));
+        assert!(RedisErrors.is_err(&refused));
+        assert!(RedisErrors.is_err(&RedisError::from((ErrorKind::ClusterDown, "down"))));
+
+        let type_error = RedisError::from((ErrorKind::TypeError, "not a string"));
+        assert!(!RedisErrors.is_err(&type_error));
+        assert!(RedisErrors.is_ignored(&type_error));
+    }
+
+    #[test]
+    fn ignore_others() {
+        let predicate = IgnoreOthers(Timeouts);
+        let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+        assert!(predicate.is_err(&timed_out));
+        assert!(!predicate.is_ignored(&timed_out));
+        assert!(predicate.is_ignored(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+
+    #[test]
+    fn fail_on_ok() {
+        let classifier = FailOnOk::new(
+            AnyError.fuse_on(|status: &u16| *status == 401),
+            |status: &u16| *status >= 500,
+        );
+        assert_eq!(Outcome::Success, classifier.classify(&Ok(200)));
+        assert_eq!(Outcome::Failure, classifier.classify(&Ok(503)));
+        assert_eq!(Outcome::Failure, classifier.classify(&Err(0)));
+        assert_eq!(Outcome::Fatal, classifier.classify(&Err(401)));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn async_std_timeouts() {
+        use std::time::Duration;
+
+        let future =
+            async_std::future::timeout(Duration::from_millis(5), std::future::pending::<()>());
+        let err = async_std::task::block_on(future).unwrap_err();
+        assert!(Timeouts.is_err(&err));
+
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(err);
+        assert!(Timeouts.is_err(&err));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn tokio_timeouts() {
+        use std::time::Duration;
+
+        let future = tokio::time::timeout(Duration::from_millis(5), std::future::pending::<()>());
+        let err = future.await.unwrap_err();
+        assert!(Timeouts.is_err(&err));
+
+        let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapper(Box::new(err)));
+        assert!(Timeouts.is_err(&err));
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn box_error_rejection() {
+        let err: tower::BoxError = Error::<io::Error>::Rejected.into();
+        assert!(Error::is_rejection(&err));
+
+        let err: tower::BoxError = Box::new(Wrapper(err));
+        assert!(Error::is_rejection(&err));
+
+        let err: tower::BoxError = Error::Inner(io::Error::from(io::ErrorKind::NotFound)).into();
+        assert!(!Error::is_rejection(&err));
+        assert!(err.is::<io::Error>());
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicU32, AtomicU64};
+
+use crate::recloser::CircuitState;
+
+/// A user provided callback.
+pub(crate) struct Hook<T: ?Sized>(Box<dyn Fn(&T) + Send + Sync>);
+
+impl<T: ?Sized> Hook<T> {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        Hook(Box::new(f))
+    }
+
+    pub(crate) fn call(&self, arg: &T) {
+        (self.0)(arg)
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Hook<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hook")
+    }
+}
+
+/// Notified of each transition between two different `CircuitState`s of a `Recloser`,
+/// see `RecloserBuilder::on_transition(...)`. It is invoked on the calling thread,
+/// right after the transition happened, so it should not block.
+pub trait TransitionListener: Send + Sync {
+    fn on_transition(&self, from: CircuitState, to: CircuitState);
+}
+
+impl<F> TransitionListener for F
+where
+    F: Fn(CircuitState, CircuitState) + Send + Sync,
+{
+    fn on_transition(&self, from: CircuitState, to: CircuitState) {
+        self(from, to)
+    }
+}
+
+/// The `TransitionListener`s registered on a `Recloser`.
+#[derive(Default)]
+pub(crate) struct Listeners(Vec<Box<dyn TransitionListener>>);
+
+impl Listeners {
+    pub(crate) fn push<L: TransitionListener + 'static>(&mut self, listener: L) {
+        self.0.push(Box::new(listener));
+    }
+
+    pub(crate) fn notify(&self, from: CircuitState, to: CircuitState) {
+        for listener in &self.0 {
+            listener.on_transition(from, to);
+        }
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Listeners({})", self.0.len())
+    }
+}
+
+/// The outcome of a call guarded by a `Recloser`, see `RecloserBuilder::on_call(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// Also covers the `Err(_)`s not considered as failures by the predicate.
+    Success,
+    Failure,
+    /// The call was rejected without being performed.
+    Rejected,
+    /// The call was performed but not recorded, see `Outcome::Ignore`.
+    Ignored,
+}
+
+/// Emitted when the failure_rate in `Closed(_)` state gets close to the threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Warning {
+    pub failure_rate: f32,
+    pub threshold: f32,
+}
+
+/// Emitted when the `Recloser` reopened too many consecutive times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Escalation {
+    /// Number of consecutive transitions from `HalfOpen(_)` to `Open(_)` state.
+    pub reopens: usize,
+}
+
+/// Fraction of the warning level the failure_rate must go below to re-arm the warning.
+const WARNING_HYSTERESIS: f32 = 0.9;
+
+/// Warns once when the failure_rate crosses `fraction` of the threshold, then
+/// waits for it to decrease again before warning anew.
+#[derive(Debug)]
+pub(crate) struct Warner {
+    fraction: f32,
+    warned: AtomicBool,
+    hook: Option<Hook<Warning>>,
+}
+
+impl Warner {
+    pub(crate) fn new(fraction: f32, hook: Option<Hook<Warning>>) -> Self {
+        Warner {
+            fraction,
+            warned: AtomicBool::new(false),
+            hook,
+        }
+    }
+
+    pub(crate) fn observe(&self, failure_rate: f32, threshold: f32) {
+        let level = self.fraction * threshold;
+
+        if failure_rate >= level {
+            if !self.warned.swap(true, Relaxed) {
+                self.warn(Warning {
+                    failure_rate,
+                    threshold,
+                });
+            }
+        } else if failure_rate < level * WARNING_HYSTERESIS {
+            self.warned.store(false, Relaxed);
+        }
+    }
+
+    pub(crate) fn rearm(&self) {
+        self.warned.store(false, Relaxed);
+    }
+
+    fn warn(&self, warning: Warning) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            failure_rate = warning.failure_rate,
+            threshold = warning.threshold,
+            "recloser failure rate is approaching its threshold"
+        );
+
+        if let Some(hook) = &self.hook {
+            hook.call(&warning);
+        }
+    }
+}
+
+/// Limits the number of calls traced per second, see `RecloserBuilder::trace_calls(...)`.
+#[cfg(feature = "tracing")]
+#[derive(Debug)]
+pub(crate) struct CallSampler {
+    per_sec: u32,
+    /// The current second, since the `origin` of the `Recloser`.
+    second: AtomicU64,
+    traced: AtomicU32,
+}
+
+#[cfg(feature = "tracing")]
+impl CallSampler {
+    pub(crate) fn new(per_sec: u32) -> Self {
+        CallSampler {
+            per_sec,
+            second: AtomicU64::new(0),
+            traced: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns whether a call made at `now` (in nanoseconds) should be traced.
+    pub(crate) fn sample(&self, now: u64) -> bool {
+        let second = now / 1_000_000_000;
+        let current = self.second.load(Relaxed);
+        if second > current
+            && self
+                .second
+                .compare_exchange(current, second, Relaxed, Relaxed)
+                .is_ok()
+        {
+            self.traced.store(0, Relaxed);
+        }
+        self.traced
+            .fetch_update(Relaxed, Relaxed, |traced| {
+                (traced < self.per_sec).then_some(traced + 1)
+            })
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn call_sampler() {
+        let sampler = CallSampler::new(2);
+        let traced = |now| (0..3).filter(|_| sampler.sample(now)).count();
+        assert_eq!(2, traced(0));
+        assert_eq!(0, traced(999_999_999));
+        assert_eq!(2, traced(1_000_000_000));
+        assert_eq!(0, traced(1_500_000_000));
+    }
+
+    #[test]
+    fn warning_hysteresis() {
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let counter = warnings.clone();
+        let warner = Warner::new(
+            0.8,
+            Some(Hook::new(move |_: &Warning| {
+                counter.fetch_add(1, Relaxed);
+            })),
+        );
+
+        warner.observe(0.3, 0.5);
+        assert_eq!(0, warnings.load(Relaxed));
+
+        warner.observe(0.4, 0.5);
+        warner.observe(0.45, 0.5);
+        assert_eq!(1, warnings.load(Relaxed));
+
+        // Still within the hysteresis band
+        warner.observe(0.37, 0.5);
+        warner.observe(0.4, 0.5);
+        assert_eq!(1, warnings.load(Relaxed));
+
+        warner.observe(0.3, 0.5);
+        warner.observe(0.4, 0.5);
+        assert_eq!(2, warnings.load(Relaxed));
+    }
+}
@@ -0,0 +1,179 @@
+//! A minimal C API, for services in other languages to share the same implementation.
+//!
+//! A `RecloserHandle` is created by `recloser_new(...)` and must be released with
+//! `recloser_free(...)`, it can be shared between threads. Calls are guarded by checking
+//! `recloser_is_call_permitted(...)` first, then reporting their outcome with
+//! `recloser_record_success(...)` or `recloser_record_failure(...)`.
+//!
+//! The crate is built as a C library with `cargo rustc --release --features ffi
+//! --crate-type cdylib`, and the header can be generated with `cbindgen`.
+
+use std::time::Duration;
+
+use crossbeam_epoch as epoch;
+
+use crate::recloser::{CircuitState, Recloser};
+
+/// An opaque `Recloser`, using the default wait strategy and considering any reported
+/// failure as such.
+pub struct RecloserHandle(Recloser);
+
+/// Same as `CircuitState`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecloserState {
+    Closed = 0,
+    Open = 1,
+    HalfOpen = 2,
+    ForcedOpen = 3,
+}
+
+impl From<CircuitState> for RecloserState {
+    fn from(state: CircuitState) -> Self {
+        match state {
+            CircuitState::Closed => RecloserState::Closed,
+            CircuitState::Open => RecloserState::Open,
+            CircuitState::HalfOpen => RecloserState::HalfOpen,
+            CircuitState::ForcedOpen => RecloserState::ForcedOpen,
+        }
+    }
+}
+
+/// Same as `Snapshot`, with negative values instead of `None`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecloserMetrics {
+    pub state: RecloserState,
+    pub failure_rate: f32,
+    pub retry_after_ms: i64,
+}
+
+/// Creates a `Recloser` with the given settings, see `RecloserBuilder`.
+#[no_mangle]
+pub extern "C" fn recloser_new(
+    error_rate: f32,
+    closed_len: usize,
+    half_open_len: usize,
+    open_wait_ms: u64,
+) -> *mut RecloserHandle {
+    let recloser = Recloser::custom()
+        .error_rate(error_rate)
+        .closed_len(closed_len)
+        .half_open_len(half_open_len)
+        .open_wait(Duration::from_millis(open_wait_ms))
+        .build();
+    Box::into_raw(Box::new(RecloserHandle(recloser)))
+}
+
+/// Creates a `Recloser` with the same settings as `Recloser::default()`.
+#[no_mangle]
+pub extern "C" fn recloser_default() -> *mut RecloserHandle {
+    Box::into_raw(Box::new(RecloserHandle(Recloser::default())))
+}
+
+/// Releases a `Recloser` created by `recloser_new(...)` or `recloser_default()`,
+/// does nothing if `recloser` is null.
+///
+/// # Safety
+///
+/// `recloser` must be null or returned by `recloser_new(...)` or `recloser_default()`,
+/// and must not be used anymore.
+#[no_mangle]
+pub unsafe extern "C" fn recloser_free(recloser: *mut RecloserHandle) {
+    if !recloser.is_null() {
+        drop(unsafe { Box::from_raw(recloser) });
+    }
+}
+
+/// Returns whether a call is permitted, `false` if `recloser` is null.
+#[no_mangle]
+pub extern "C" fn recloser_is_call_permitted(recloser: Option<&RecloserHandle>) -> bool {
+    recloser.is_some_and(|recloser| recloser.0.call_permitted(&epoch::pin()))
+}
+
+/// Records a permitted call that succeeded, does nothing if `recloser` is null.
+#[no_mangle]
+pub extern "C" fn recloser_record_success(recloser: Option<&RecloserHandle>) {
+    if let Some(recloser) = recloser {
+        recloser.0.on_success(&epoch::pin());
+        recloser.0.release_detached();
+    }
+}
+
+/// Records a permitted call that failed, does nothing if `recloser` is null.
+#[no_mangle]
+pub extern "C" fn recloser_record_failure(recloser: Option<&RecloserHandle>) {
+    if let Some(recloser) = recloser {
+        recloser.0.on_error(&epoch::pin());
+        recloser.0.release_detached();
+    }
+}
+
+/// Returns the current state, `ForcedOpen` if `recloser` is null as calls are never
+/// permitted then.
+#[no_mangle]
+pub extern "C" fn recloser_state(recloser: Option<&RecloserHandle>) -> RecloserState {
+    recloser.map_or(RecloserState::ForcedOpen, |recloser| {
+        recloser.0.state().into()
+    })
+}
+
+/// Writes the current metrics into `metrics`, returns `false` if either pointer is null.
+#[no_mangle]
+pub extern "C" fn recloser_metrics(
+    recloser: Option<&RecloserHandle>,
+    metrics: Option<&mut RecloserMetrics>,
+) -> bool {
+    let (Some(recloser), Some(metrics)) = (recloser, metrics) else {
+        return false;
+    };
+    let snapshot = recloser.0.snapshot();
+    *metrics = RecloserMetrics {
+        state: snapshot.state.into(),
+        failure_rate: snapshot.failure_rate.unwrap_or(-1.0),
+        retry_after_ms: snapshot
+            .retry_after
+            .map_or(-1, |retry_after| retry_after.as_millis() as i64),
+    };
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn c_api() {
+        let recloser = recloser_new(0.5, 2, 1, 1000);
+        let handle = unsafe { recloser.as_ref() };
+
+        for _ in 0..3 {
+            assert!(recloser_is_call_permitted(handle));
+            recloser_record_failure(handle);
+        }
+        assert!(!recloser_is_call_permitted(handle));
+        assert_eq!(RecloserState::Open, recloser_state(handle));
+
+        let mut metrics = RecloserMetrics {
+            state: RecloserState::Closed,
+            failure_rate: 0.0,
+            retry_after_ms: 0,
+        };
+        assert!(recloser_metrics(handle, Some(&mut metrics)));
+        assert_eq!(RecloserState::Open, metrics.state);
+        assert_eq!(-1.0, metrics.failure_rate);
+        assert_eq!(1000, metrics.retry_after_ms);
+
+        assert!(!recloser_is_call_permitted(None));
+        assert_eq!(RecloserState::ForcedOpen, recloser_state(None));
+        assert!(!recloser_metrics(None, Some(&mut metrics)));
+        recloser_record_success(None);
+
+        unsafe {
+            recloser_free(recloser);
+            recloser_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::error::{Classifier, Outcome};
+
+/// The gRPC status codes considered as failures by default.
+const UNAVAILABLE: u32 = 14;
+const DEADLINE_EXCEEDED: u32 = 4;
+const RESOURCE_EXHAUSTED: u32 = 8;
+const INTERNAL: u32 = 13;
+
+/// Classifies raw HTTP responses of gRPC calls from their `grpc-status` header, by
+/// default `UNAVAILABLE`, `DEADLINE_EXCEEDED`, `RESOURCE_EXHAUSTED` and `INTERNAL` are
+/// failures. Responses with a non `2xx` HTTP status and transport errors always are.
+///
+/// The header is only present on trailers-only responses, that is when the call failed
+/// before any message was sent, statuses sent in trailers can't be seen here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcClassifier {
+    failures: Vec<u32>,
+}
+
+impl Default for GrpcClassifier {
+    fn default() -> Self {
+        GrpcClassifier {
+            failures: vec![UNAVAILABLE, DEADLINE_EXCEEDED, RESOURCE_EXHAUSTED, INTERNAL],
+        }
+    }
+}
+
+impl GrpcClassifier {
+    pub fn new() -> Self {
+        GrpcClassifier::default()
+    }
+
+    /// A classifier that does not consider any gRPC status code as a failure, to be
+    /// configured with `fail_on(...)`.
+    pub fn none() -> Self {
+        GrpcClassifier {
+            failures: Vec::new(),
+        }
+    }
+
+    /// Also considers the gRPC status `code` as a failure.
+    pub fn fail_on(mut self, code: u32) -> Self {
+        self.failures.push(code);
+        self
+    }
+
+    pub fn is_failure(&self, code: u32) -> bool {
+        self.failures.contains(&code)
+    }
+
+    /// Returns the gRPC status code found in `headers`, `None` if missing or invalid.
+    pub fn status(headers: &http::HeaderMap) -> Option<u32> {
+        headers.get("grpc-status")?.to_str().ok()?.parse().ok()
+    }
+}
+
+impl<B, E> Classifier<http::Response<B>, E> for GrpcClassifier {
+    fn classify(&self, res: &Result<http::Response<B>, E>) -> Outcome {
+        match res {
+            Ok(res) if !res.status().is_success() => Outcome::Failure,
+            Ok(res) => match GrpcClassifier::status(res.headers()) {
+                Some(code) if self.is_failure(code) => Outcome::Failure,
+                _ => Outcome::Success,
+            },
+            Err(_) => Outcome::Failure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(grpc_status: Option<&str>) -> Result<http::Response<()>, ()> {
+        let mut builder = http::Response::builder();
+        if let Some(grpc_status) = grpc_status {
+            builder = builder.header("grpc-status", grpc_status);
+        }
+        Ok(builder.body(()).unwrap())
+    }
+
+    #[test]
+    fn grpc_classifier() {
+        let classifier = GrpcClassifier::new();
+        assert_eq!(Outcome::Success, classifier.classify(&response(None)));
+        assert_eq!(Outcome::Success, classifier.classify(&response(Some("0"))));
+        assert_eq!(Outcome::Success, classifier.classify(&response(Some("5"))));
+        assert_eq!(Outcome::Failure, classifier.classify(&response(Some("14"))));
+        assert_eq!(Outcome::Success, classifier.classify(&response(Some("?"))));
+        assert_eq!(
+            Outcome::Failure,
+            classifier.classify(&Err::<http::Response<()>, _>(()))
+        );
+
+        let classifier = GrpcClassifier::none().fail_on(5);
+        assert_eq!(Outcome::Failure, classifier.classify(&response(Some("5"))));
+        assert_eq!(Outcome::Success, classifier.classify(&response(Some("14"))));
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::AnyError;
+use crate::metrics::{HealthStatus, Metrics, OpenDurations, RollingStats, StateDurations};
+use crate::r#async::AsyncRecloser;
+use crate::recloser::{CircuitState, Recloser, Rejection, Snapshot};
+use crate::subscription::TransitionEvent;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// A cheap to clone, read only view of a `Recloser`, typically to be handed to
+/// monitoring code. It only exposes the observational methods, so that calls can
+/// neither be recorded nor rejected through it.
+#[derive(Debug)]
+pub struct RecloserHandle<W = DefaultWait, P = AnyError> {
+    inner: Arc<Recloser<W, P>>,
+}
+
+impl<W, P> Clone for RecloserHandle<W, P> {
+    fn clone(&self) -> Self {
+        RecloserHandle {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<W, P> From<Arc<Recloser<W, P>>> for RecloserHandle<W, P> {
+    fn from(inner: Arc<Recloser<W, P>>) -> Self {
+        RecloserHandle { inner }
+    }
+}
+
+impl<W: WaitStrategy, P> AsyncRecloser<W, P> {
+    /// Returns a read only `RecloserHandle` of the underlying `Recloser`.
+    pub fn handle(&self) -> RecloserHandle<W, P> {
+        RecloserHandle::from(self.inner.clone())
+    }
+}
+
+impl<W: WaitStrategy, P> RecloserHandle<W, P> {
+    /// Same as `Recloser::state()`.
+    pub fn state(&self) -> CircuitState {
+        self.inner.state()
+    }
+
+    /// Same as `Recloser::rejection()`.
+    pub fn rejection(&self) -> Option<Rejection> {
+        self.inner.rejection()
+    }
+
+    /// Same as `Recloser::time_until_half_open()`.
+    pub fn time_until_half_open(&self) -> Option<Duration> {
+        self.inner.time_until_half_open()
+    }
+
+    /// Same as `Recloser::snapshot()`.
+    pub fn snapshot(&self) -> Snapshot {
+        self.inner.snapshot()
+    }
+
+    /// Same as `Recloser::metrics()`.
+    pub fn metrics(&self) -> Metrics {
+        self.inner.metrics()
+    }
+
+    /// Same as `Recloser::rolling_stats()`.
+    pub fn rolling_stats(&self) -> Vec<RollingStats> {
+        self.inner.rolling_stats()
+    }
+
+    /// Same as `Recloser::state_durations()`.
+    pub fn state_durations(&self) -> StateDurations {
+        self.inner.state_durations()
+    }
+
+    /// Same as `Recloser::open_durations()`.
+    pub fn open_durations(&self) -> OpenDurations {
+        self.inner.open_durations()
+    }
+
+    /// Same as `Recloser::health()`.
+    pub fn health(&self) -> f32 {
+        self.inner.health()
+    }
+
+    /// Same as `Recloser::health_status()`.
+    pub fn health_status(&self) -> HealthStatus {
+        self.inner.health_status()
+    }
+
+    /// Same as `Recloser::trip_error()`.
+    pub fn trip_error(&self) -> Option<String> {
+        self.inner.trip_error()
+    }
+
+    /// Same as `Recloser::history()`.
+    pub fn history(&self) -> Vec<TransitionEvent> {
+        self.inner.history()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_view() {
+        let recloser = Recloser::custom().closed_len(1).history(1).build();
+        let recloser = AsyncRecloser::from(recloser);
+        let handle = recloser.handle().clone();
+        assert_eq!(CircuitState::Closed, handle.state());
+
+        for _ in 0..2 {
+            let _ = recloser.inner.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, handle.state());
+        assert_eq!(2, handle.metrics().failures);
+        assert_eq!(0.0, handle.health());
+        assert!(handle.rejection().is_some());
+        assert_eq!(
+            Some(CircuitState::Open),
+            handle.history().first().map(|event| event.to)
+        );
+    }
+}
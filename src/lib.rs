@@ -1,13 +1,113 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "admin")]
+mod admin;
+#[cfg(feature = "ureq")]
+mod agent;
+#[cfg(feature = "proptest")]
+mod arbitrary;
 mod r#async;
+#[cfg(feature = "tokio")]
+mod channel;
+mod command;
+mod config;
+mod diagram;
 mod error;
+mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tonic")]
+mod grpc;
+mod handle;
+mod metrics;
+#[cfg(feature = "async-nats")]
+mod nats;
+mod pattern;
+mod policy;
+#[cfg(feature = "mobc")]
+mod pool;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "pyo3")]
+pub mod python;
 mod recloser;
+mod registry;
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+mod report;
+#[cfg(feature = "tokio")]
+mod reporter;
 mod ring_buffer;
+mod rolling;
+#[cfg(feature = "tarpc")]
+mod rpc;
+mod status;
+#[cfg(feature = "stream")]
+mod stream;
+mod subscription;
+#[cfg(feature = "tokio")]
+mod supervisor;
+#[cfg(feature = "trace")]
+mod trace;
+mod wait;
 
-pub use crate::error::{AnyError, Error, ErrorPredicate};
-pub use crate::r#async::{AsyncRecloser, RecloserFuture};
-pub use crate::recloser::{Recloser, RecloserBuilder};
+#[cfg(feature = "admin")]
+pub use crate::admin::AdminService;
+#[cfg(feature = "ureq")]
+pub use crate::agent::GuardedAgent;
+#[cfg(feature = "proptest")]
+pub use crate::arbitrary::{check_invariants, steps, Step};
+#[cfg(feature = "tokio")]
+pub use crate::channel::{GuardedBroadcastSender, GuardedSendError, GuardedSender};
+pub use crate::command::{Command, ConfigUpdate};
+pub use crate::config::RecloserConfig;
+#[cfg(feature = "redis")]
+pub use crate::error::RedisErrors;
+#[cfg(any(feature = "tower", feature = "tarpc"))]
+pub use crate::error::RejectedError;
+#[cfg(feature = "sqlx")]
+pub use crate::error::SqlxErrors;
+pub use crate::error::{
+    AnyError, Categorize, CausedBy, Classifier, DescribeWith, Error, ErrorKinds, ErrorPredicate,
+    FailOnOk, FuseOn, IgnoreOn, IgnoreOthers, Outcome, SlowerThan, Timeouts, Weighted,
+};
+pub use crate::events::{CallOutcome, Escalation, TransitionListener, Warning};
+#[cfg(feature = "tonic")]
+pub use crate::grpc::GrpcClassifier;
+pub use crate::handle::RecloserHandle;
+pub use crate::metrics::{HealthStatus, Metrics, OpenDurations, RollingStats, StateDurations};
+#[cfg(feature = "async-nats")]
+pub use crate::nats::GuardedClient;
+pub use crate::pattern::{Pattern, Predicate};
+pub use crate::policy::{Slo, TripPolicy};
+#[cfg(feature = "mobc")]
+pub use crate::pool::GuardedManager;
+pub use crate::r#async::{AsyncRecloser, DefaultPredicate, RecloserFuture};
+pub use crate::recloser::{CircuitState, Recloser, RecloserBuilder, Rejection, Snapshot};
+pub use crate::registry::Registry;
+#[cfg(feature = "anyhow")]
+pub use crate::report::AnyhowPredicate;
+#[cfg(feature = "eyre")]
+pub use crate::report::EyrePredicate;
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+pub use crate::report::{Report, ReportPredicate};
+#[cfg(feature = "tokio")]
+pub use crate::reporter::Sink;
+#[cfg(feature = "tarpc")]
+pub use crate::rpc::GuardedStub;
+pub use crate::status::StatusClassifier;
+#[cfg(feature = "stream")]
+pub use crate::stream::Windowed;
+pub use crate::subscription::{Overflow, Receiver, TransitionCause, TransitionEvent};
+#[cfg(feature = "trace")]
+pub use crate::trace::{read_trace, Event, Replay, TraceRecorder};
+pub use crate::wait::{BoxedWait, DefaultWait, Schedule, WaitContext, WaitStrategy};
+
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::registry::Registration;
+    pub use inventory;
+}
 
 #[cfg(doctest)]
 mod doctests {
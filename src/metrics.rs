@@ -0,0 +1,558 @@
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::time::Duration;
+
+use crate::recloser::{CircuitState, Recloser};
+use crate::rolling::RollingCounter;
+use crate::wait::WaitStrategy;
+
+/// Cumulative counts of the calls guarded by a `Recloser`, see `Recloser::metrics()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Metrics {
+    /// All calls, permitted or rejected.
+    pub calls: u64,
+    /// Permitted calls recorded as successes, including the `Err(_)`s not considered
+    /// as failures by the predicate.
+    pub successes: u64,
+    /// Permitted calls recorded as failures.
+    pub failures: u64,
+    /// Calls rejected without being performed.
+    pub rejected: u64,
+    /// Permitted calls currently being performed, not included in `calls`.
+    pub in_flight: u64,
+    /// Same as `Snapshot::failure_rate`.
+    pub failure_rate: Option<f32>,
+}
+
+/// Counts of the calls performed over a rolling window, see `Recloser::rolling_stats()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct RollingStats {
+    /// As configured with `RecloserBuilder::rolling_window(...)`.
+    pub window: Duration,
+    pub calls: u64,
+    pub failures: u64,
+    /// Failure rate over the window, `None` if no calls were performed.
+    pub failure_rate: Option<f32>,
+}
+
+/// A coarse health of a `Recloser`, to be reported to health check frameworks, for
+/// instance as a Kubernetes readiness probe, see `Recloser::health_status()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum HealthStatus {
+    Healthy,
+    /// Calls are permitted again, but the recovery is not confirmed yet.
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    /// Returns whether calls are permitted, that is unless `Unhealthy`.
+    pub fn is_ready(self) -> bool {
+        self != HealthStatus::Unhealthy
+    }
+}
+
+impl From<CircuitState> for HealthStatus {
+    fn from(state: CircuitState) -> Self {
+        match state {
+            CircuitState::Closed => HealthStatus::Healthy,
+            CircuitState::HalfOpen => HealthStatus::Degraded,
+            CircuitState::Open | CircuitState::ForcedOpen => HealthStatus::Unhealthy,
+        }
+    }
+}
+
+/// The counters behind `Metrics` and `RollingStats`.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    successes: AtomicU64,
+    failures: AtomicU64,
+    rejected: AtomicU64,
+    in_flight: AtomicU64,
+    rolling: Vec<(Duration, RollingCounter)>,
+}
+
+/// Decrements the in-flight gauge when dropped, see `Counters::start()`.
+pub(crate) struct InFlight<'a>(&'a Counters);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+impl Counters {
+    pub(crate) fn new(windows: Vec<Duration>) -> Self {
+        Counters {
+            rolling: windows
+                .into_iter()
+                .map(|window| (window, RollingCounter::new(window)))
+                .collect(),
+            ..Counters::default()
+        }
+    }
+
+    /// Counts a successful call made at `now`, in nanoseconds of the `Recloser` clock.
+    pub(crate) fn success(&self, now: u64) {
+        self.successes.fetch_add(1, Relaxed);
+        for (_, counter) in &self.rolling {
+            counter.record(now, false);
+        }
+    }
+
+    /// Same as `success(...)`, for a failed call.
+    pub(crate) fn failure(&self, now: u64) {
+        self.failures.fetch_add(1, Relaxed);
+        for (_, counter) in &self.rolling {
+            counter.record(now, true);
+        }
+    }
+
+    pub(crate) fn rejection(&self) {
+        self.rejected.fetch_add(1, Relaxed);
+    }
+
+    /// Counts a permitted call as in-flight, until the returned guard is dropped.
+    pub(crate) fn start(&self) -> InFlight<'_> {
+        self.start_detached();
+        InFlight(self)
+    }
+
+    /// Same as `start()`, for `Future`s that cannot hold the guard across polls,
+    /// `finish()` must be called once done.
+    pub(crate) fn start_detached(&self) {
+        self.in_flight.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn finish(&self) {
+        self.in_flight.fetch_sub(1, Relaxed);
+    }
+
+    /// Returns the current counts, along with the given `failure_rate`.
+    pub(crate) fn metrics(&self, failure_rate: Option<f32>) -> Metrics {
+        let successes = self.successes.load(Relaxed);
+        let failures = self.failures.load(Relaxed);
+        let rejected = self.rejected.load(Relaxed);
+        Metrics {
+            calls: successes + failures + rejected,
+            successes,
+            failures,
+            rejected,
+            in_flight: self.in_flight.load(Relaxed),
+            failure_rate,
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [&self.successes, &self.failures, &self.rejected] {
+            counter.store(0, Relaxed);
+        }
+        for (_, counter) in &self.rolling {
+            counter.reset();
+        }
+    }
+}
+
+/// Cumulative time spent in each `CircuitState`, see `Recloser::state_durations()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct StateDurations {
+    pub closed: Duration,
+    pub open: Duration,
+    /// Also covers the single call allowed by `suspend_probe(...)`.
+    pub half_open: Duration,
+    pub forced_open: Duration,
+}
+
+impl StateDurations {
+    /// Returns the share of time spent in `Closed(_)` state, `None` if no time elapsed.
+    pub fn availability(&self) -> Option<f32> {
+        let total = self.closed + self.open + self.half_open + self.forced_open;
+        if total.is_zero() {
+            None
+        } else {
+            Some((self.closed.as_secs_f64() / total.as_secs_f64()) as f32)
+        }
+    }
+}
+
+/// The distribution of the time spent in `Open(_)` state, see `Recloser::open_durations()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OpenDurations {
+    /// Number of periods spent in `Open(_)` state, excluding the ongoing one.
+    pub count: u64,
+    /// Total time spent in `Open(_)` state, including the ongoing period.
+    pub total: Duration,
+    /// Number of completed periods per bucket, along with the upper bound of the bucket,
+    /// the last one being `Duration::MAX`.
+    pub buckets: Vec<(Duration, u64)>,
+}
+
+/// Upper bounds of the `OpenDurations` buckets.
+const OPEN_BUCKETS: [Duration; 10] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::MAX,
+];
+
+/// The time spent in each state before the current one, in nanoseconds, indexed by
+/// `CircuitState`.
+#[derive(Debug, Default)]
+pub(crate) struct Uptime {
+    /// When the current state was entered.
+    entered: AtomicU64,
+    spent: [AtomicU64; 4],
+    opened: [AtomicU64; OPEN_BUCKETS.len()],
+}
+
+impl Uptime {
+    /// Accounts for the time spent in `from` state, left at `now`, and returns it.
+    pub(crate) fn leave(&self, from: CircuitState, now: u64) -> Duration {
+        let since = self.entered.swap(now, Relaxed);
+        let spent = now.saturating_sub(since);
+        self.spent[from as usize].fetch_add(spent, Relaxed);
+        let spent = Duration::from_nanos(spent);
+        if from == CircuitState::Open {
+            let bucket = OPEN_BUCKETS.iter().position(|&le| spent <= le);
+            self.opened[bucket.unwrap_or(OPEN_BUCKETS.len() - 1)].fetch_add(1, Relaxed);
+        }
+        spent
+    }
+
+    /// Forgets the time spent so far, as if the current state was entered at `now`.
+    fn reset(&self, now: u64) {
+        self.entered.store(now, Relaxed);
+        for spent in self.spent.iter().chain(&self.opened) {
+            spent.store(0, Relaxed);
+        }
+    }
+}
+
+impl<W: WaitStrategy, P> Recloser<W, P> {
+    /// Returns the counts of calls since the `Recloser` was built, or since the last
+    /// `reset_metrics()`. They are not affected by `reset()` nor by transitions.
+    pub fn metrics(&self) -> Metrics {
+        self.snapshot().metrics
+    }
+
+    /// Zeroes all the observational counters, that is `metrics()`, `rolling_stats()`,
+    /// `state_durations()`, `open_durations()` and the transition counts, typically to
+    /// align them with a metrics collection interval. The windows used to decide
+    /// transitions, as well as the `history()`, are left untouched.
+    pub fn reset_metrics(&self) {
+        self.counters.reset();
+        self.uptime.reset(self.now());
+        self.reset_transitions();
+    }
+
+    /// Returns the statistics of each window configured with
+    /// `RecloserBuilder::rolling_window(...)`, in the same order. Unlike the `Closed(_)`
+    /// `RingBuffer`, they are not reset by transitions.
+    pub fn rolling_stats(&self) -> Vec<RollingStats> {
+        let now = self.now();
+        self.counters
+            .rolling
+            .iter()
+            .map(|(window, counter)| {
+                let (calls, failures) = counter.totals(now);
+                RollingStats {
+                    window: *window,
+                    calls,
+                    failures,
+                    failure_rate: counter.failure_rate(now),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns how long the `Recloser` stayed in `Open(_)` state each time it tripped,
+    /// to quantify the outages of the guarded dependency.
+    pub fn open_durations(&self) -> OpenDurations {
+        let buckets = OPEN_BUCKETS
+            .iter()
+            .zip(&self.uptime.opened)
+            .map(|(&le, count)| (le, count.load(Relaxed)))
+            .collect::<Vec<_>>();
+        OpenDurations {
+            count: buckets.iter().map(|(_, count)| count).sum(),
+            total: self.state_durations().open,
+            buckets,
+        }
+    }
+
+    /// Returns a score between `0.0` and `1.0`, typically to be used as a load balancing
+    /// weight or a readiness signal. It lies within `0.5..=1.0` in `Closed(_)` state and
+    /// within `0.0..=0.5` in `HalfOpen(_)` state, decreasing as the failure rate gets
+    /// closer to the threshold, and is `0.0` when calls are rejected.
+    pub fn health(&self) -> f32 {
+        let snapshot = self.snapshot();
+        let margin = |threshold: f32| match snapshot.failure_rate {
+            Some(failure_rate) if threshold > 0.0 => 1.0 - (failure_rate / threshold).min(1.0),
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+        match snapshot.state {
+            CircuitState::Closed => 0.5 + 0.5 * margin(self.threshold()),
+            CircuitState::HalfOpen => 0.5 * margin(self.half_open_threshold()),
+            CircuitState::Open | CircuitState::ForcedOpen => 0.0,
+        }
+    }
+
+    /// Returns the `HealthStatus` derived from the current state.
+    pub fn health_status(&self) -> HealthStatus {
+        self.state().into()
+    }
+
+    /// Returns the time spent in each state since the `Recloser` was built, including
+    /// the ongoing time in the current state, so that the availability of the guarded
+    /// dependency can be derived from it.
+    pub fn state_durations(&self) -> StateDurations {
+        let uptime = &self.uptime;
+        let mut spent = uptime.spent.each_ref().map(|spent| spent.load(Relaxed));
+        let ongoing = self.now().saturating_sub(uptime.entered.load(Relaxed));
+        spent[self.state() as usize] += ongoing;
+        let [closed, open, half_open, forced_open] = spent.map(Duration::from_nanos);
+        StateDurations {
+            closed,
+            open,
+            half_open,
+            forced_open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_clock::FakeClock;
+
+    use super::*;
+    use crate::error::ErrorPredicate;
+
+    #[test]
+    fn metrics() {
+        let recloser = Recloser::custom()
+            .closed_len(2)
+            .error_rate(0.9)
+            .default_predicate(|err: &bool| *err)
+            .build();
+
+        let _ = recloser.call(|| Ok::<(), bool>(()));
+        let _ = recloser.call(|| Err::<(), bool>(false));
+        let _ = recloser.call(|| Err::<(), bool>(true));
+        assert_eq!(
+            Metrics {
+                calls: 3,
+                successes: 2,
+                failures: 1,
+                rejected: 0,
+                in_flight: 0,
+                failure_rate: Some(0.5),
+            },
+            recloser.metrics()
+        );
+
+        let fuse = |err: &bool| *err;
+        let _ = recloser.call_with(fuse.fuse_on(|_: &bool| true), || Err::<(), bool>(true));
+        let _ = recloser.call(|| Ok::<(), bool>(()));
+        recloser.reset();
+
+        let metrics = recloser.metrics();
+        assert_eq!(
+            (5, 2, 2, 1),
+            (
+                metrics.calls,
+                metrics.successes,
+                metrics.failures,
+                metrics.rejected
+            )
+        );
+        assert_eq!(None, metrics.failure_rate);
+    }
+
+    #[test]
+    fn in_flight() {
+        let recloser = Recloser::default();
+        let in_flight = recloser.call(|| Ok::<_, ()>(recloser.metrics().in_flight));
+        assert!(matches!(in_flight, Ok(1)));
+        assert_eq!(0, recloser.metrics().in_flight);
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            recloser.call(|| -> Result<(), ()> { panic!("boom") })
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(0, recloser.metrics().in_flight);
+    }
+
+    #[test]
+    fn reset_metrics() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .rolling_window(Duration::from_secs(10))
+            .build();
+        for _ in 0..3 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        FakeClock::advance_time(1000);
+
+        recloser.reset_metrics();
+        let metrics = recloser.metrics();
+        assert_eq!(
+            (0, 0, 0),
+            (metrics.calls, metrics.failures, metrics.rejected)
+        );
+        assert_eq!(0, recloser.rolling_stats()[0].calls);
+        assert_eq!(StateDurations::default(), recloser.state_durations());
+        assert_eq!(
+            0,
+            recloser.transitions(CircuitState::Closed, CircuitState::Open)
+        );
+
+        // The trip window is kept
+        assert_eq!(CircuitState::Open, recloser.state());
+        FakeClock::advance_time(500);
+        assert_eq!(Duration::from_millis(500), recloser.state_durations().open);
+    }
+
+    #[test]
+    fn rolling_stats() {
+        let recloser = Recloser::custom()
+            .error_rate(0.9)
+            .rolling_window(Duration::from_secs(10))
+            .rolling_window(Duration::from_secs(60))
+            .build();
+        assert!(Recloser::default().rolling_stats().is_empty());
+
+        let _ = recloser.call(|| Err::<(), ()>(()));
+        FakeClock::advance_time(20_000);
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        recloser.reset();
+
+        let stats = recloser
+            .rolling_stats()
+            .iter()
+            .map(|stats| (stats.window.as_secs(), stats.calls, stats.failure_rate))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(10, 1, Some(0.0)), (60, 2, Some(0.5))], stats);
+    }
+
+    #[test]
+    fn open_durations() {
+        let recloser = Recloser::default();
+        recloser.open_for(Duration::from_secs(2));
+        FakeClock::advance_time(2500);
+        recloser.reset();
+        recloser.trip();
+        FakeClock::advance_time(45_000);
+        recloser.force_open();
+        recloser.open_for(Duration::from_secs(60));
+        FakeClock::advance_time(1000);
+
+        let durations = recloser.open_durations();
+        assert_eq!(2, durations.count);
+        assert_eq!(Duration::from_millis(48_500), durations.total);
+        let buckets = durations
+            .buckets
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![&(Duration::from_secs(5), 1), &(Duration::from_secs(60), 1)],
+            buckets
+        );
+        assert_eq!(Duration::MAX, durations.buckets.last().unwrap().0);
+    }
+
+    #[test]
+    fn health() {
+        let recloser = Recloser::custom()
+            .closed_len(4)
+            .half_open_len(2)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        assert_eq!(1.0, recloser.health());
+
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        let _ = recloser.call(|| Err::<(), ()>(()));
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        assert_eq!(0.75, recloser.health());
+
+        recloser.trip();
+        assert_eq!(0.0, recloser.health());
+
+        FakeClock::advance_time(1500);
+        let _ = recloser.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::HalfOpen, recloser.state());
+        assert_eq!(0.0, recloser.health());
+
+        recloser.force_open();
+        assert_eq!(0.0, recloser.health());
+        recloser.reset();
+        assert_eq!(1.0, recloser.health());
+    }
+
+    #[test]
+    fn health_status() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        assert_eq!(HealthStatus::Healthy, recloser.health_status());
+
+        for _ in 0..2 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(HealthStatus::Unhealthy, recloser.health_status());
+        assert!(!recloser.health_status().is_ready());
+
+        FakeClock::advance_time(1500);
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        assert_eq!(HealthStatus::Degraded, recloser.health_status());
+        assert!(recloser.health_status().is_ready());
+    }
+
+    #[test]
+    fn state_durations() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        assert_eq!(None, recloser.state_durations().availability());
+
+        FakeClock::advance_time(3000);
+        for _ in 0..2 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        FakeClock::advance_time(1500);
+        let _ = recloser.call(|| Ok::<(), ()>(()));
+        FakeClock::advance_time(500);
+
+        let durations = recloser.state_durations();
+        assert_eq!(
+            StateDurations {
+                closed: Duration::from_secs(3),
+                open: Duration::from_millis(1500),
+                half_open: Duration::from_millis(500),
+                forced_open: Duration::ZERO,
+            },
+            durations
+        );
+        assert_eq!(Some(0.6), durations.availability());
+    }
+}
@@ -0,0 +1,173 @@
+use async_nats::client::{PublishError, PublishErrorKind, RequestError, RequestErrorKind};
+use async_nats::subject::ToSubject;
+use async_nats::{Client, HeaderMap, Message, Request};
+use bytes::Bytes;
+
+use crate::error::{AnyError, Error};
+use crate::r#async::AsyncRecloser;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// An `async_nats::Client` whose requests and publications are guarded by an
+/// `AsyncRecloser`, classifying errors by their kind rather than with the default
+/// predicate.
+///
+/// Requests that time out or find no responders are failures, while invalid requests
+/// are not. Publications only fail when the client cannot send anymore.
+///
+/// ```rust,ignore
+/// use recloser::{AsyncRecloser, GuardedClient, Recloser};
+///
+/// let client = async_nats::connect("demo.nats.io").await?;
+/// let client = GuardedClient::new(client, AsyncRecloser::from(Recloser::default()));
+/// let res = client.request("service", "data".into()).await;
+/// ```
+#[derive(Debug)]
+pub struct GuardedClient<W = DefaultWait, P = AnyError> {
+    client: Client,
+    recloser: AsyncRecloser<W, P>,
+}
+
+impl<W, P> Clone for GuardedClient<W, P> {
+    fn clone(&self) -> Self {
+        GuardedClient {
+            client: self.client.clone(),
+            recloser: self.recloser.clone(),
+        }
+    }
+}
+
+fn request_failed(err: &RequestError) -> bool {
+    matches!(
+        err.kind(),
+        RequestErrorKind::TimedOut | RequestErrorKind::NoResponders
+    )
+}
+
+fn publish_failed(err: &PublishError) -> bool {
+    matches!(err.kind(), PublishErrorKind::Send)
+}
+
+impl<W: WaitStrategy, P> GuardedClient<W, P> {
+    pub fn new(client: Client, recloser: AsyncRecloser<W, P>) -> Self {
+        GuardedClient { client, recloser }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn recloser(&self) -> &AsyncRecloser<W, P> {
+        &self.recloser
+    }
+
+    /// Same as `Client::request(...)`, returns `Err(Error::Rejected)` without sending
+    /// the request when the `Recloser` is open.
+    pub async fn request<S: ToSubject>(
+        &self,
+        subject: S,
+        payload: Bytes,
+    ) -> Result<Message, Error<RequestError>> {
+        self.send_request(subject, Request::new().payload(payload))
+            .await
+    }
+
+    /// Same as `Client::request_with_headers(...)`, see `request(...)`.
+    pub async fn request_with_headers<S: ToSubject>(
+        &self,
+        subject: S,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> Result<Message, Error<RequestError>> {
+        let request = Request::new().headers(headers).payload(payload);
+        self.send_request(subject, request).await
+    }
+
+    /// Same as `Client::send_request(...)`, see `request(...)`.
+    pub async fn send_request<S: ToSubject>(
+        &self,
+        subject: S,
+        request: Request,
+    ) -> Result<Message, Error<RequestError>> {
+        let subject = subject.to_subject();
+        self.recloser
+            .call_async_with(request_failed, async || {
+                self.client.send_request(subject, request).await
+            })
+            .await
+    }
+
+    /// Same as `Client::publish(...)`, returns `Err(Error::Rejected)` without publishing
+    /// when the `Recloser` is open.
+    pub async fn publish<S: ToSubject>(
+        &self,
+        subject: S,
+        payload: Bytes,
+    ) -> Result<(), Error<PublishError>> {
+        let subject = subject.to_subject();
+        self.recloser
+            .call_async_with(publish_failed, async || {
+                self.client.publish(subject, payload).await
+            })
+            .await
+    }
+
+    /// Same as `Client::publish_with_headers(...)`, see `publish(...)`.
+    pub async fn publish_with_headers<S: ToSubject>(
+        &self,
+        subject: S,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> Result<(), Error<PublishError>> {
+        let subject = subject.to_subject();
+        self.recloser
+            .call_async_with(publish_failed, async || {
+                self.client
+                    .publish_with_headers(subject, headers, payload)
+                    .await
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_nats::ConnectOptions;
+
+    use super::*;
+    use crate::recloser::{CircuitState, Recloser};
+
+    #[tokio::test]
+    async fn guarded_client() {
+        // Nothing listens on this port, so that requests time out
+        let client = ConnectOptions::new()
+            .retry_on_initial_connect()
+            .request_timeout(Some(Duration::from_millis(10)))
+            .connect("127.0.0.1:1")
+            .await
+            .unwrap();
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+        let client = GuardedClient::new(client, recloser);
+
+        for _ in 0..2 {
+            let err = client.request("service", "data".into()).await.unwrap_err();
+            assert!(matches!(err, Error::Inner(e) if e.kind() == RequestErrorKind::TimedOut));
+        }
+        assert_eq!(CircuitState::Open, client.recloser().state());
+        assert!(matches!(
+            client.request("service", "data".into()).await,
+            Err(Error::Rejected)
+        ));
+        assert!(matches!(
+            client.publish("events", "data".into()).await,
+            Err(Error::Rejected)
+        ));
+
+        let err = RequestError::new(RequestErrorKind::Other);
+        assert!(!request_failed(&err));
+        assert!(request_failed(&RequestError::new(
+            RequestErrorKind::NoResponders
+        )));
+    }
+}
@@ -0,0 +1,108 @@
+use std::mem::Discriminant;
+use std::ops::RangeInclusive;
+
+use crate::error::ErrorPredicate;
+
+/// A value matched against errors of type `E` by a `Predicate`.
+pub trait Pattern<E> {
+    fn matches(&self, err: &E) -> bool;
+}
+
+/// Matches equal values, such as status codes.
+impl<T: PartialEq> Pattern<T> for T {
+    fn matches(&self, err: &T) -> bool {
+        self == err
+    }
+}
+
+impl<T: PartialOrd> Pattern<T> for RangeInclusive<T> {
+    fn matches(&self, err: &T) -> bool {
+        self.contains(err)
+    }
+}
+
+/// Matches the variant of an enum, as returned by `std::mem::discriminant(...)`.
+impl<E> Pattern<E> for Discriminant<E> {
+    fn matches(&self, err: &E) -> bool {
+        std::mem::discriminant(err) == *self
+    }
+}
+
+impl Pattern<std::io::Error> for std::io::ErrorKind {
+    fn matches(&self, err: &std::io::Error) -> bool {
+        err.kind() == *self
+    }
+}
+
+/// Considers the errors matching any of its patterns as failures, to declare simple
+/// policies as data rather than closures.
+///
+/// With the `serde` feature, it (de)serializes as the list of its patterns, for instance
+/// `[429, [500, 599]]` with status codes and ranges of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Predicate<K> {
+    patterns: Vec<K>,
+}
+
+impl<K> Predicate<K> {
+    pub fn any_of(patterns: impl IntoIterator<Item = K>) -> Self {
+        Predicate {
+            patterns: patterns.into_iter().collect(),
+        }
+    }
+
+    pub fn patterns(&self) -> &[K] {
+        &self.patterns
+    }
+}
+
+impl<E, K: Pattern<E>> ErrorPredicate<E> for Predicate<K> {
+    fn is_err(&self, err: &E) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug)]
+    enum Failure {
+        Timeout,
+        Refused,
+        Invalid,
+    }
+
+    #[test]
+    fn any_of() {
+        let statuses = Predicate::any_of([429..=429, 500..=599]);
+        assert!(statuses.is_err(&503));
+        assert!(!statuses.is_err(&404));
+
+        let kinds = Predicate::any_of([io::ErrorKind::TimedOut, io::ErrorKind::ConnectionReset]);
+        assert!(kinds.is_err(&io::Error::from(io::ErrorKind::TimedOut)));
+        assert!(!kinds.is_err(&io::Error::from(io::ErrorKind::NotFound)));
+
+        let variants = Predicate::any_of([
+            std::mem::discriminant(&Failure::Timeout),
+            std::mem::discriminant(&Failure::Refused),
+        ]);
+        assert!(variants.is_err(&Failure::Timeout));
+        assert!(!variants.is_err(&Failure::Invalid));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_predicate() {
+        let statuses: Predicate<u16> = serde_json::from_str("[429, 503]").unwrap();
+        assert!(statuses.is_err(&503));
+        assert_eq!(&[429, 503], statuses.patterns());
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::ring_buffer::RingBuffer;
+use crate::rolling::RollingCounter;
+
+/// Trips when the failure rate increases by at least `delta` within `within`,
+/// even though the absolute threshold has not been reached.
+#[derive(Debug)]
+pub(crate) struct RateJump {
+    delta: f32,
+    /// In nanoseconds, as `now` given to `observe(...)`.
+    within: u64,
+    /// The lowest failure rate recently observed, and when it was observed.
+    lowest: Mutex<Option<(f32, u64)>>,
+}
+
+impl RateJump {
+    pub(crate) fn new(delta: f32, within: Duration) -> Self {
+        RateJump {
+            delta,
+            within: within.as_nanos() as u64,
+            lowest: Mutex::new(None),
+        }
+    }
+
+    /// Records a `failure_rate` calculated at `now`, in nanoseconds of the `Recloser`
+    /// clock, returns whether it jumped by at least `delta` compared to the lowest
+    /// recent one.
+    pub(crate) fn observe(&self, now: u64, failure_rate: f32) -> bool {
+        let mut lowest = self.lowest.lock().unwrap_or_else(|e| e.into_inner());
+
+        match *lowest {
+            Some((rate, at)) if now < at.saturating_add(self.within) => {
+                if failure_rate <= rate {
+                    *lowest = Some((failure_rate, now));
+                }
+                failure_rate - rate >= self.delta
+            }
+            _ => {
+                *lowest = Some((failure_rate, now));
+                false
+            }
+        }
+    }
+
+    /// Forgets previously observed failure rates.
+    pub(crate) fn reset(&self) {
+        *self.lowest.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// A category of errors with its own window and threshold in `Closed(_)` state, see
+/// `RecloserBuilder::category(...)`.
+#[derive(Debug)]
+pub(crate) struct Category {
+    pub(crate) name: &'static str,
+    threshold: f32,
+    rb: RingBuffer,
+}
+
+impl Category {
+    pub(crate) fn new(name: &'static str, len: usize, threshold: f32) -> Self {
+        Category {
+            name,
+            threshold,
+            rb: RingBuffer::new(len),
+        }
+    }
+
+    /// Records a call, a failure weighing `weight` when it is in this category, returns
+    /// whether the failure rate of the category reached its threshold.
+    pub(crate) fn record(&self, weight: f32) -> bool {
+        let failure_rate = self.rb.set_weighted(weight);
+        failure_rate > -1.0 && failure_rate >= self.threshold
+    }
+
+    pub(crate) fn reset(&self) {
+        self.rb.reset();
+    }
+}
+
+/// Minimum number of observed failure rates before the baseline is used.
+const ANOMALY_MIN_SAMPLES: u64 = 30;
+
+/// Lower bound of the standard deviation, a perfectly steady baseline would
+/// otherwise consider any change as an anomaly.
+const ANOMALY_MIN_STDDEV: f64 = 0.01;
+
+/// Lower bound of the mean when the threshold is relative to it, a baseline
+/// without failures would otherwise consider any failure as an anomaly.
+const ANOMALY_MIN_MEAN: f64 = 0.01;
+
+/// Learns a baseline failure rate (exponentially weighted mean and variance)
+/// and considers failure rates deviating too much from it as anomalies.
+#[derive(Debug)]
+pub(crate) struct Anomaly {
+    deviation: Deviation,
+    horizon: Duration,
+    baseline: Mutex<Baseline>,
+}
+
+/// How far from the baseline a failure rate is an anomaly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Deviation {
+    /// By more than this many standard deviations above the mean.
+    ZScore(f32),
+    /// At least this many times the mean.
+    Ratio(f32),
+}
+
+#[derive(Debug, Default)]
+struct Baseline {
+    mean: f64,
+    variance: f64,
+    samples: u64,
+    /// When it was last updated, in nanoseconds of the `Recloser` clock.
+    updated: Option<u64>,
+}
+
+impl Baseline {
+    fn threshold(&self, deviation: Deviation) -> Option<f32> {
+        if self.samples < ANOMALY_MIN_SAMPLES {
+            return None;
+        }
+        let threshold = match deviation {
+            Deviation::ZScore(z_score) => {
+                let stddev = self.variance.sqrt().max(ANOMALY_MIN_STDDEV);
+                self.mean + z_score as f64 * stddev
+            }
+            Deviation::Ratio(ratio) => self.mean.max(ANOMALY_MIN_MEAN) * ratio as f64,
+        };
+        Some(threshold as f32)
+    }
+}
+
+impl Anomaly {
+    pub(crate) fn new(deviation: Deviation, horizon: Duration) -> Self {
+        Anomaly {
+            deviation,
+            horizon,
+            baseline: Mutex::new(Baseline::default()),
+        }
+    }
+
+    /// Returns the current dynamic threshold, `None` while still learning.
+    pub(crate) fn threshold(&self) -> Option<f32> {
+        self.baseline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .threshold(self.deviation)
+    }
+
+    /// Returns the dynamic threshold the `failure_rate` should be compared to,
+    /// `None` while still learning. Failure rates that are not anomalies are
+    /// then used to update the baseline, as of `now` in nanoseconds of the
+    /// `Recloser` clock.
+    pub(crate) fn observe(&self, now: u64, failure_rate: f32) -> Option<f32> {
+        let mut baseline = self.baseline.lock().unwrap_or_else(|e| e.into_inner());
+
+        let threshold = baseline.threshold(self.deviation);
+        if matches!(threshold, Some(threshold) if failure_rate >= threshold) {
+            return threshold;
+        }
+
+        // Time based decay, but behaves as a cumulative average while few
+        // samples have been observed.
+        let elapsed = match baseline.updated {
+            Some(updated) if now > updated => Duration::from_nanos(now - updated).as_secs_f64(),
+            _ => 0.0,
+        };
+        let decay = 1.0 - (-elapsed / self.horizon.as_secs_f64()).exp();
+        let alpha = decay.max(1.0 / (baseline.samples + 1) as f64);
+
+        let diff = failure_rate as f64 - baseline.mean;
+        let incr = alpha * diff;
+        baseline.mean += incr;
+        baseline.variance = (1.0 - alpha) * (baseline.variance + diff * incr);
+        baseline.samples += 1;
+        baseline.updated = Some(now);
+
+        threshold
+    }
+}
+
+/// A combination of conditions under which a `Recloser` trips in `Closed(_)` state, in
+/// place of the fixed `error_rate`, see `RecloserBuilder::trip_policy(...)`.
+///
+/// Trips are caused by `TransitionCause::Policy` with the name of the clause that fired.
+/// Leaf clauses are named after their constructor, an `or(...)` reports whichever of
+/// its clauses fired first and an `and(...)` reports its first clause, unless they were
+/// given another name with `named(...)`. Slow calls can be counted as failures with
+/// `SlowerThan`, so that `slow_call_rate(...)` is evaluated after them.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use recloser::{Recloser, TripPolicy};
+///
+/// let policy = TripPolicy::failure_rate(0.5)
+///     .or(TripPolicy::consecutive_failures(10))
+///     .or(TripPolicy::slow_call_rate(0.8, Duration::from_secs(2)))
+///     .or(TripPolicy::failure_rate(0.2)
+///         .and(TripPolicy::failure_count(50))
+///         .named("sustained"));
+///
+/// let recloser = Recloser::custom().trip_policy(policy).build();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripPolicy {
+    name: Option<&'static str>,
+    clause: Clause,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    FailureRate(f32),
+    FailureCount(f32),
+    ConsecutiveFailures(u32),
+    SlowCallRate(f32, Duration),
+    Or(Box<TripPolicy>, Box<TripPolicy>),
+    And(Box<TripPolicy>, Box<TripPolicy>),
+}
+
+/// What a `TripPolicy` is evaluated against, after a failed call in `Closed(_)` state.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Observed<'a> {
+    /// The failure_rate of the window, `-1.0` while it is not calculated yet.
+    pub(crate) failure_rate: f32,
+    /// The weighted number of failures of the window.
+    pub(crate) failures: f32,
+    pub(crate) consecutive_failures: u32,
+    /// The durations of the latest timed calls, if the policy needs them.
+    pub(crate) slow_calls: Option<&'a SlowCalls>,
+}
+
+impl TripPolicy {
+    fn new(clause: Clause) -> Self {
+        TripPolicy { name: None, clause }
+    }
+
+    /// Fires when the failure_rate of the window reaches `rate`.
+    pub fn failure_rate(rate: f32) -> Self {
+        TripPolicy::new(Clause::FailureRate(rate))
+    }
+
+    /// Fires when the window holds at least `count` failures, weighted as by
+    /// `ErrorPredicate::weight(...)`.
+    pub fn failure_count(count: u32) -> Self {
+        TripPolicy::new(Clause::FailureCount(count as f32))
+    }
+
+    /// Fires after `count` failed calls in a row.
+    pub fn consecutive_failures(count: u32) -> Self {
+        TripPolicy::new(Clause::ConsecutiveFailures(count))
+    }
+
+    /// Fires when the rate of calls slower than `slower_than` reaches `rate`, among the
+    /// latest `closed_len` calls timed by `call_classified(...)` in `Closed(_)` state.
+    pub fn slow_call_rate(rate: f32, slower_than: Duration) -> Self {
+        TripPolicy::new(Clause::SlowCallRate(rate, slower_than))
+    }
+
+    /// Fires when either this policy or `other` fires.
+    pub fn or(self, other: TripPolicy) -> Self {
+        TripPolicy::new(Clause::Or(Box::new(self), Box::new(other)))
+    }
+
+    /// Fires when both this policy and `other` fire.
+    pub fn and(self, other: TripPolicy) -> Self {
+        TripPolicy::new(Clause::And(Box::new(self), Box::new(other)))
+    }
+
+    /// Reports `name` as the clause that fired when this policy fires.
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Whether a clause needs the durations of the calls.
+    pub(crate) fn timed(&self) -> bool {
+        match &self.clause {
+            Clause::SlowCallRate(..) => true,
+            Clause::Or(left, right) | Clause::And(left, right) => left.timed() || right.timed(),
+            _ => false,
+        }
+    }
+
+    /// Returns the name of the clause that fired, if any.
+    pub(crate) fn fired(&self, observed: &Observed) -> Option<&'static str> {
+        let fired = match &self.clause {
+            Clause::FailureRate(rate) => (observed.failure_rate > -1.0
+                && observed.failure_rate >= *rate)
+                .then_some("failure_rate"),
+            Clause::FailureCount(count) => (observed.failures >= *count).then_some("failure_count"),
+            Clause::ConsecutiveFailures(count) => {
+                (observed.consecutive_failures >= *count).then_some("consecutive_failures")
+            }
+            Clause::SlowCallRate(rate, slower_than) => observed
+                .slow_calls
+                .and_then(|slow_calls| slow_calls.rate(*slower_than))
+                .is_some_and(|slow_rate| slow_rate >= *rate)
+                .then_some("slow_call_rate"),
+            Clause::Or(left, right) => left.fired(observed).or_else(|| right.fired(observed)),
+            Clause::And(left, right) => match (left.fired(observed), right.fired(observed)) {
+                (Some(name), Some(_)) => Some(name),
+                _ => None,
+            },
+        };
+        fired.map(|name| self.name.unwrap_or(name))
+    }
+}
+
+/// The durations of the latest calls timed in `Closed(_)` state, see
+/// `TripPolicy::slow_call_rate(...)`.
+#[derive(Debug)]
+pub(crate) struct SlowCalls {
+    len: usize,
+    durations: Mutex<VecDeque<Duration>>,
+}
+
+impl SlowCalls {
+    pub(crate) fn new(len: usize) -> Self {
+        SlowCalls {
+            len: len.max(1),
+            durations: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let mut durations = self.durations.lock().unwrap_or_else(|e| e.into_inner());
+        if durations.len() == self.len {
+            durations.pop_front();
+        }
+        durations.push_back(elapsed);
+    }
+
+    /// Returns the rate of calls slower than `slower_than`, `None` until `len` calls
+    /// were timed.
+    pub(crate) fn rate(&self, slower_than: Duration) -> Option<f32> {
+        let durations = self.durations.lock().unwrap_or_else(|e| e.into_inner());
+        if durations.len() < self.len {
+            return None;
+        }
+        let slow = durations.iter().filter(|d| **d > slower_than).count();
+        Some(slow as f32 / self.len as f32)
+    }
+
+    pub(crate) fn reset(&self) {
+        self.durations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+/// An error budget policy based on a service level objective.
+///
+/// The burn rate is how fast the error budget is consumed: a burn rate of `1.0`
+/// consumes exactly the budget allowed by the `target` over time. The `Recloser`
+/// trips when, for any configured pair of windows, the burn rate over both the
+/// long and the short window reaches the associated multiplier.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use recloser::{Recloser, Slo};
+///
+/// let slo = Slo::new(0.999)
+///     .burn_rate(14.4, Duration::from_secs(3600), Duration::from_secs(300))
+///     .burn_rate(6.0, Duration::from_secs(6 * 3600), Duration::from_secs(1800));
+///
+/// let recloser = Recloser::custom().slo(slo).build();
+/// ```
+#[derive(Debug)]
+pub struct Slo {
+    target: f32,
+    windows: Vec<BurnWindow>,
+}
+
+#[derive(Debug)]
+struct BurnWindow {
+    multiplier: f32,
+    long: RollingCounter,
+    short: RollingCounter,
+}
+
+impl Slo {
+    /// Creates a policy for the given success ratio `target`, e.g. `0.999`, which
+    /// must lie strictly between `0.0` and `1.0` so that some error budget is left.
+    pub fn new(target: f32) -> Self {
+        assert!(
+            0.0 < target && target < 1.0,
+            "Slo target must be in (0.0, 1.0), got {target}"
+        );
+        Slo {
+            target,
+            windows: Vec::new(),
+        }
+    }
+
+    /// Adds a pair of windows over which the burn rate must reach `multiplier`.
+    pub fn burn_rate(mut self, multiplier: f32, long: Duration, short: Duration) -> Self {
+        self.windows.push(BurnWindow {
+            multiplier,
+            long: RollingCounter::new(long),
+            short: RollingCounter::new(short),
+        });
+        self
+    }
+
+    /// Records a call made at `now`, in nanoseconds of the `Recloser` clock.
+    pub(crate) fn record(&self, now: u64, failed: bool) {
+        for w in self.windows.iter() {
+            w.long.record(now, failed);
+            w.short.record(now, failed);
+        }
+    }
+
+    /// Returns whether the error budget is burning too fast at `now`.
+    pub(crate) fn burning(&self, now: u64) -> bool {
+        let budget = 1.0 - self.target;
+        let burn_rate =
+            |counter: &RollingCounter| counter.failure_rate(now).unwrap_or(0.0) / budget;
+
+        self.windows
+            .iter()
+            .any(|w| burn_rate(&w.long) >= w.multiplier && burn_rate(&w.short) >= w.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_jump() {
+        let jump = RateJump::new(0.3, Duration::from_secs(10));
+
+        assert!(!jump.observe(0, 0.1));
+        assert!(!jump.observe(0, 0.2));
+        assert!(!jump.observe(0, 0.0));
+        assert!(jump.observe(0, 0.3));
+
+        // Slow increases are not considered as jumps
+        jump.reset();
+        for i in 0..10 {
+            assert!(!jump.observe(i * 11_000_000_000, i as f32 / 10.0));
+        }
+    }
+
+    #[test]
+    fn anomaly_baseline() {
+        let anomaly = Anomaly::new(Deviation::ZScore(3.0), Duration::from_secs(3600));
+
+        for i in 0..ANOMALY_MIN_SAMPLES {
+            let rate = if i % 2 == 0 { 0.18 } else { 0.22 };
+            assert_eq!(None, anomaly.observe(0, rate));
+        }
+
+        let threshold = anomaly.threshold().unwrap();
+        assert!(threshold > 0.25 && threshold < 0.3, "{}", threshold);
+
+        // Anomalies are not learned
+        assert!(anomaly.observe(0, 0.5).unwrap() <= threshold);
+        assert_eq!(threshold, anomaly.threshold().unwrap());
+        assert!(anomaly.observe(0, 0.2).unwrap() <= threshold);
+    }
+
+    #[test]
+    fn anomaly_ratio() {
+        let anomaly = Anomaly::new(Deviation::Ratio(5.0), Duration::from_secs(3600));
+
+        for _ in 0..ANOMALY_MIN_SAMPLES {
+            assert_eq!(None, anomaly.observe(0, 0.04));
+        }
+        let threshold = anomaly.threshold().unwrap();
+        assert!((threshold - 0.2).abs() < 1e-6, "{}", threshold);
+
+        // A baseline without failures still needs some failures to trip
+        let anomaly = Anomaly::new(Deviation::Ratio(5.0), Duration::from_secs(3600));
+        for _ in 0..ANOMALY_MIN_SAMPLES {
+            anomaly.observe(0, 0.0);
+        }
+        assert!((anomaly.threshold().unwrap() - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn trip_policy() {
+        let policy = TripPolicy::failure_rate(0.5)
+            .or(TripPolicy::consecutive_failures(3))
+            .or(TripPolicy::failure_rate(0.2)
+                .and(TripPolicy::failure_count(5))
+                .named("sustained"));
+        let observed = |failure_rate, failures, consecutive_failures| Observed {
+            failure_rate,
+            failures,
+            consecutive_failures,
+            slow_calls: None,
+        };
+
+        assert_eq!(None, policy.fired(&observed(-1.0, 2.0, 2)));
+        assert_eq!(Some("failure_rate"), policy.fired(&observed(0.5, 2.0, 1)));
+        assert_eq!(
+            Some("consecutive_failures"),
+            policy.fired(&observed(-1.0, 3.0, 3))
+        );
+        assert_eq!(None, policy.fired(&observed(0.2, 4.0, 1)));
+        assert_eq!(None, policy.fired(&observed(0.1, 5.0, 1)));
+        assert_eq!(Some("sustained"), policy.fired(&observed(0.2, 5.0, 1)));
+    }
+
+    #[test]
+    fn slow_call_rate() {
+        let policy = TripPolicy::slow_call_rate(0.5, Duration::from_secs(1));
+        let slow_calls = SlowCalls::new(4);
+        let observed = |slow_calls| Observed {
+            failure_rate: -1.0,
+            failures: 1.0,
+            consecutive_failures: 1,
+            slow_calls,
+        };
+        assert!(policy.timed());
+        assert!(!TripPolicy::failure_rate(0.5).timed());
+
+        for ms in [2000, 100, 1500] {
+            slow_calls.record(Duration::from_millis(ms));
+        }
+        assert_eq!(None, policy.fired(&observed(None)));
+        assert_eq!(None, policy.fired(&observed(Some(&slow_calls))));
+
+        slow_calls.record(Duration::from_millis(100));
+        assert_eq!(
+            Some("slow_call_rate"),
+            policy.fired(&observed(Some(&slow_calls)))
+        );
+
+        slow_calls.record(Duration::from_millis(100));
+        assert_eq!(None, policy.fired(&observed(Some(&slow_calls))));
+    }
+
+    #[test]
+    fn slo_burn_rate() {
+        let slo = Slo::new(0.9).burn_rate(2.0, Duration::from_secs(60), Duration::from_secs(5));
+        let second = 1_000_000_000;
+
+        // Burn rate of 1.0 over both windows
+        for i in 0..60 {
+            slo.record(i * second, i % 10 == 0);
+        }
+        let now = 60 * second;
+        assert!(!slo.burning(now));
+
+        // Burn rate of 5.0 over the short window only
+        for _ in 0..5 {
+            slo.record(now, false);
+            slo.record(now, true);
+        }
+        assert!(!slo.burning(now));
+
+        // Burn rate reaches 2.0 over the long window too
+        for _ in 0..10 {
+            slo.record(now, true);
+        }
+        assert!(slo.burning(now));
+    }
+
+    #[test]
+    #[should_panic(expected = "Slo target")]
+    fn slo_no_budget() {
+        Slo::new(1.0);
+    }
+}
@@ -0,0 +1,118 @@
+use std::future::Future;
+
+use mobc::{async_trait, Manager};
+
+use crate::error::{AnyError, Error, ErrorPredicate};
+use crate::r#async::AsyncRecloser;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// A `mobc::Manager` whose connection creations and health checks are guarded by an
+/// `AsyncRecloser`, classifying errors with its default predicate.
+///
+/// While the `Recloser` is open, the pool fails fast with `Error::Rejected` instead of
+/// hammering an unavailable backend with new connections.
+///
+/// ```rust,ignore
+/// use recloser::{AsyncRecloser, GuardedManager, Recloser};
+///
+/// let recloser = AsyncRecloser::from(Recloser::default());
+/// let pool = mobc::Pool::builder().build(GuardedManager::new(manager, recloser));
+/// ```
+#[derive(Debug)]
+pub struct GuardedManager<M, W = DefaultWait, P = AnyError> {
+    manager: M,
+    recloser: AsyncRecloser<W, P>,
+}
+
+impl<M, W, P> GuardedManager<M, W, P> {
+    pub fn new(manager: M, recloser: AsyncRecloser<W, P>) -> Self {
+        GuardedManager { manager, recloser }
+    }
+
+    /// The `AsyncRecloser` guarding the pool, for instance to report its state along
+    /// with the pool metrics.
+    pub fn recloser(&self) -> &AsyncRecloser<W, P> {
+        &self.recloser
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.manager
+    }
+}
+
+#[async_trait]
+impl<M, W, P> Manager for GuardedManager<M, W, P>
+where
+    M: Manager,
+    W: WaitStrategy + Send + Sync + 'static,
+    P: ErrorPredicate<M::Error> + Send + Sync + 'static,
+{
+    type Connection = M::Connection;
+    type Error = Error<M::Error>;
+
+    fn spawn_task<T>(&self, task: T)
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.manager.spawn_task(task)
+    }
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        self.recloser.call(self.manager.connect()).await
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        self.recloser.call(self.manager.check(conn)).await
+    }
+
+    fn validate(&self, conn: &mut Self::Connection) -> bool {
+        self.manager.validate(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+    use super::*;
+    use crate::recloser::{CircuitState, Recloser};
+
+    struct Flaky(AtomicBool);
+
+    #[async_trait]
+    impl Manager for Flaky {
+        type Connection = ();
+        type Error = ();
+
+        async fn connect(&self) -> Result<(), ()> {
+            if self.0.load(Relaxed) {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn check(&self, conn: ()) -> Result<(), ()> {
+            Ok(conn)
+        }
+    }
+
+    #[tokio::test]
+    async fn guarded_manager() {
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+        let manager = GuardedManager::new(Flaky(AtomicBool::new(true)), recloser);
+
+        for _ in 0..2 {
+            assert!(matches!(manager.connect().await, Err(Error::Inner(()))));
+        }
+        assert_eq!(CircuitState::Open, manager.recloser().state());
+
+        manager.inner().0.store(false, Relaxed);
+        let pool = mobc::Pool::builder().max_open(1).build(manager);
+        assert!(matches!(
+            pool.get().await,
+            Err(mobc::Error::Inner(Error::Rejected))
+        ));
+    }
+}
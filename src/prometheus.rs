@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use crate::recloser::{CircuitState, Snapshot};
+use crate::registry::Registry;
+
+const STATES: [(CircuitState, &str); 4] = [
+    (CircuitState::Closed, "closed"),
+    (CircuitState::Open, "open"),
+    (CircuitState::HalfOpen, "half_open"),
+    (CircuitState::ForcedOpen, "forced_open"),
+];
+
+impl Registry {
+    /// Renders the `Snapshot`s of all the registered `Recloser`s in the Prometheus text
+    /// exposition format, labelled by name, so that it can be served by any HTTP server.
+    pub fn render_prometheus(&self) -> String {
+        render(&self.snapshots())
+    }
+}
+
+fn render(snapshots: &[(String, Snapshot)]) -> String {
+    let mut out = String::new();
+    family(
+        &mut out,
+        "state",
+        "gauge",
+        "Whether the recloser is in the given state.",
+        snapshots.iter().flat_map(|(name, snapshot)| {
+            STATES.iter().map(move |(state, label)| {
+                let value = (snapshot.state == *state) as u8;
+                format!(r#"{{name="{}",state="{label}"}} {value}"#, escape(name))
+            })
+        }),
+    );
+    family(
+        &mut out,
+        "failure_rate",
+        "gauge",
+        "Failure rate of the calls recorded in the current state.",
+        snapshots.iter().filter_map(|(name, snapshot)| {
+            let rate = snapshot.failure_rate?;
+            Some(format!(r#"{{name="{}"}} {rate}"#, escape(name)))
+        }),
+    );
+    family(
+        &mut out,
+        "calls_total",
+        "counter",
+        "Calls guarded by the recloser, by outcome.",
+        snapshots.iter().flat_map(|(name, snapshot)| {
+            let metrics = snapshot.metrics;
+            [
+                ("success", metrics.successes),
+                ("failure", metrics.failures),
+                ("rejected", metrics.rejected),
+            ]
+            .into_iter()
+            .map(move |(outcome, count)| {
+                format!(r#"{{name="{}",outcome="{outcome}"}} {count}"#, escape(name))
+            })
+        }),
+    );
+    family(
+        &mut out,
+        "in_flight",
+        "gauge",
+        "Calls currently being performed through the recloser.",
+        snapshots.iter().map(|(name, snapshot)| {
+            let in_flight = snapshot.metrics.in_flight;
+            format!(r#"{{name="{}"}} {in_flight}"#, escape(name))
+        }),
+    );
+    family(
+        &mut out,
+        "trips_total",
+        "counter",
+        "Transitions of the recloser to the open state.",
+        snapshots
+            .iter()
+            .map(|(name, snapshot)| format!(r#"{{name="{}"}} {}"#, escape(name), snapshot.flaps)),
+    );
+    out
+}
+
+/// Writes a metric family, `samples` being its labels and values.
+fn family(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl Iterator<Item = String>,
+) {
+    let _ = writeln!(out, "# HELP recloser_{name} {help}");
+    let _ = writeln!(out, "# TYPE recloser_{name} {kind}");
+    for sample in samples {
+        let _ = writeln!(out, "recloser_{name}{sample}");
+    }
+}
+
+/// Escapes a label value as required by the text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recloser::Recloser;
+
+    #[test]
+    fn render_prometheus() {
+        let registry = Registry::new();
+        let recloser = Recloser::custom().closed_len(1).build();
+        for _ in 0..3 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        registry.register(r#"a "quoted" name"#, recloser);
+        registry.register("idle", Recloser::default());
+
+        let rendered = registry.render_prometheus();
+        for line in [
+            "# TYPE recloser_state gauge",
+            r#"recloser_state{name="a \"quoted\" name",state="open"} 1"#,
+            r#"recloser_state{name="a \"quoted\" name",state="closed"} 0"#,
+            r#"recloser_state{name="idle",state="closed"} 1"#,
+            r#"recloser_calls_total{name="a \"quoted\" name",outcome="failure"} 2"#,
+            r#"recloser_calls_total{name="a \"quoted\" name",outcome="rejected"} 1"#,
+            r#"recloser_in_flight{name="idle"} 0"#,
+            "# TYPE recloser_trips_total counter",
+            r#"recloser_trips_total{name="a \"quoted\" name"} 1"#,
+        ] {
+            assert!(rendered.lines().any(|l| l == line), "{line} in {rendered}");
+        }
+        assert!(!rendered.contains("recloser_failure_rate{"));
+    }
+}
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_epoch as epoch;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+
+use crate::config::RecloserConfig;
+use crate::recloser::{CircuitState, Recloser};
+
+create_exception!(
+    recloser,
+    RejectedError,
+    PyException,
+    "Raised instead of performing a call rejected by a Recloser."
+);
+
+/// A `Recloser` for Python, configured with the same settings as a `RecloserConfig`,
+/// `open_wait` being a number of seconds. Any exception raised by a guarded call is
+/// considered as a failure.
+///
+/// ```python
+/// from recloser import Recloser, RejectedError
+///
+/// recloser = Recloser(error_rate=0.3, open_wait=10.0)
+///
+/// with recloser:
+///     call_service()
+///
+/// @recloser
+/// def call_other_service():
+///     ...
+/// ```
+#[pyclass(name = "Recloser", module = "recloser", frozen)]
+pub struct PyRecloser {
+    inner: Arc<Recloser>,
+}
+
+#[pymethods]
+impl PyRecloser {
+    #[new]
+    #[pyo3(signature = (
+        error_rate = 0.5,
+        closed_len = 100,
+        half_open_len = 10,
+        half_open_min_calls = None,
+        open_wait = 30.0,
+    ))]
+    fn new(
+        error_rate: f32,
+        closed_len: usize,
+        half_open_len: usize,
+        half_open_min_calls: Option<usize>,
+        open_wait: f64,
+    ) -> PyResult<Self> {
+        let open_wait = Duration::try_from_secs_f64(open_wait)
+            .map_err(|err| PyValueError::new_err(format!("invalid open_wait: {err}")))?;
+        let config = RecloserConfig {
+            error_rate,
+            closed_len,
+            half_open_len,
+            half_open_min_calls,
+            open_wait,
+            ..RecloserConfig::default()
+        };
+        Ok(PyRecloser {
+            inner: Arc::new(config.builder().build()),
+        })
+    }
+
+    /// One of `"closed"`, `"open"`, `"half_open"` or `"forced_open"`.
+    #[getter]
+    fn state(&self) -> &'static str {
+        match self.inner.state() {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+            CircuitState::ForcedOpen => "forced_open",
+        }
+    }
+
+    /// Calls `f` with the given arguments, raises `RejectedError` without calling it
+    /// when the `Recloser` is open.
+    #[pyo3(signature = (f, *args, **kwargs))]
+    fn call<'py>(
+        &self,
+        f: &Bound<'py, PyAny>,
+        args: &Bound<'py, PyTuple>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        guarded_call(&self.inner, f, args, kwargs)
+    }
+
+    fn force_open(&self) {
+        self.inner.force_open()
+    }
+
+    fn force_close(&self) -> bool {
+        self.inner.force_close()
+    }
+
+    fn reset(&self) {
+        self.inner.reset()
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        if !slf.inner.call_permitted(&epoch::pin()) {
+            return Err(RejectedError::new_err("call rejected by recloser"));
+        }
+        Ok(slf)
+    }
+
+    fn __exit__(
+        &self,
+        exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        let guard = &epoch::pin();
+        match exc_type.is_none() {
+            true => self.inner.on_success(guard),
+            false => self.inner.on_error(guard),
+        }
+        self.inner.release_detached();
+        false
+    }
+
+    /// Decorates `f` so that all its calls are guarded.
+    fn __call__(&self, f: Py<PyAny>) -> Guarded {
+        Guarded {
+            recloser: self.inner.clone(),
+            f,
+        }
+    }
+}
+
+/// A function decorated by a `Recloser`.
+#[pyclass(module = "recloser", frozen)]
+pub struct Guarded {
+    recloser: Arc<Recloser>,
+    f: Py<PyAny>,
+}
+
+#[pymethods]
+impl Guarded {
+    #[pyo3(signature = (*args, **kwargs))]
+    fn __call__<'py>(
+        &self,
+        args: &Bound<'py, PyTuple>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        guarded_call(&self.recloser, self.f.bind(args.py()), args, kwargs)
+    }
+}
+
+fn guarded_call<'py>(
+    recloser: &Recloser,
+    f: &Bound<'py, PyAny>,
+    args: &Bound<'py, PyTuple>,
+    kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    recloser
+        .call(|| f.call(args, kwargs))
+        .map_err(|err| match err {
+            crate::Error::Inner(err) => err,
+            crate::Error::Rejected => RejectedError::new_err("call rejected by recloser"),
+        })
+}
+
+/// The `recloser` Python module, see `PyRecloser`.
+#[pymodule]
+pub fn recloser(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRecloser>()?;
+    m.add_class::<Guarded>()?;
+    m.add("RejectedError", m.py().get_type::<RejectedError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+    use pyo3::wrap_pymodule;
+
+    use super::*;
+
+    #[test]
+    fn python_module() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = wrap_pymodule!(recloser)(py);
+            let locals = [("recloser", module)].into_py_dict(py).unwrap();
+            py.run(
+                cr#"
+r = recloser.Recloser(closed_len=1, open_wait=60.0)
+
+@r
+def fail(msg):
+    raise KeyError(msg)
+
+for _ in range(2):
+    try:
+        fail("boom")
+    except KeyError:
+        pass
+assert r.state == "open"
+
+try:
+    with r:
+        raise AssertionError("permitted")
+except recloser.RejectedError:
+    pass
+
+r.reset()
+with r:
+    pass
+assert r.call(lambda x, y=1: x + y, 1, y=2) == 3
+assert r.state == "closed"
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let err = PyRecloser::new(0.5, 100, 10, None, -1.0).err().unwrap();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+}
@@ -3,296 +3,3468 @@ use fake_clock::FakeClock as Instant;
 #[cfg(not(test))]
 use std::time::Instant;
 
-use std::sync::atomic::Ordering::{Acquire, Release};
-use std::time::Duration;
+use std::sync::atomic::{
+    AtomicBool, AtomicU32, AtomicU64, AtomicUsize,
+    Ordering::{AcqRel, Acquire, Relaxed},
+};
+use std::time::{Duration, SystemTime};
 
 use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned};
 
-use crate::error::{AnyError, Error, ErrorPredicate};
-use crate::ring_buffer::RingBuffer;
+use crate::error::{AnyError, Classifier, Error, ErrorPredicate, Outcome};
+#[cfg(feature = "tracing")]
+use crate::events::CallSampler;
+use crate::events::{
+    CallOutcome, Escalation, Hook, Listeners, TransitionListener, Warner, Warning,
+};
+use crate::metrics::{Counters, Metrics, Uptime};
+use crate::policy::{Anomaly, Category, Deviation, Observed, RateJump, Slo, SlowCalls, TripPolicy};
+use crate::ring_buffer::{RingBuffer, TimeWindow};
+use crate::rolling::RollingCounter;
+use crate::subscription::{History, Subscribers, TransitionCause, TransitionEvent};
+use crate::wait::{DefaultWait, WaitContext, WaitStrategy};
 
 /// A concurrent cirbuit breaker based on `RingBuffer`s that allows or rejects
 /// calls depending on the state it is in.
+///
+/// The time spent in `Open(_)` state is determined by a `WaitStrategy`, it defaults
+/// to `DefaultWait` that always waits for the same duration. Plain `call(...)`s are
+/// classified by an `ErrorPredicate`, it defaults to `AnyError`.
 #[derive(Debug)]
-pub struct Recloser {
-    threshold: f32,
-    closed_len: usize,
-    half_open_len: usize,
-    open_wait: Duration,
-    state: Atomic<State>,
+pub struct Recloser<W = DefaultWait, P = AnyError> {
+    name: Option<String>,
+    #[cfg(feature = "tracing")]
+    trace_calls: Option<CallSampler>,
+    #[cfg(feature = "tokio-metrics")]
+    pub(crate) task_monitor: Option<tokio_metrics::TaskMonitor>,
+    /// The `error_rate` as `f32` bits, so that it can be updated at runtime.
+    threshold: AtomicU32,
+    wait: W,
+    pub(crate) predicate: P,
+    rate_jump: Option<RateJump>,
+    anomaly: Option<Anomaly>,
+    slo: Option<Slo>,
+    trip_policy: Option<TripPolicy>,
+    /// Set when the `trip_policy` has a `slow_call_rate(...)` clause.
+    slow_calls: Option<SlowCalls>,
+    /// Failed calls in a row in `Closed(_)` state.
+    consecutive_failures: AtomicU32,
+    categories: Vec<Category>,
+    warner: Option<Warner>,
+    suspend_probe: Option<u32>,
+    escalation: Option<EscalationPolicy>,
+    /// Reopens after which `ForcedOpen` state is latched, and the reopens within the window.
+    flap_latch: Option<(u64, RollingCounter)>,
+    listeners: Listeners,
+    on_call: Option<Hook<CallOutcome>>,
+    pub(crate) subscribers: Subscribers,
+    pub(crate) history: Option<History>,
+    pub(crate) counters: Counters,
+    pub(crate) uptime: Uptime,
+    trip_error: std::sync::Mutex<Option<String>>,
+    /// Category of the failed call that caused the latest transition to `Open(_)` state.
+    open_category: std::sync::Mutex<Option<&'static str>>,
+    half_open_min_calls: Option<usize>,
+    half_open_successes: Option<usize>,
+    /// Consecutive successful calls since `HalfOpen(_)` state was entered.
+    successes: AtomicUsize,
+    probes: Probes,
+    fuse_wait: Duration,
+    /// Time added to the clock while replaying a trace.
+    #[cfg(any(feature = "trace", feature = "proptest"))]
+    skew: AtomicU64,
+    #[cfg(feature = "tokio")]
+    cancel: std::sync::Mutex<Option<tokio_util::sync::CancellationToken>>,
+    #[cfg(feature = "tokio")]
+    pub(crate) watch: tokio::sync::watch::Sender<CircuitState>,
+    reopens: AtomicUsize,
+    /// Whether it is in shadow mode, see `set_disabled(...)`.
+    disabled: AtomicBool,
+    /// Time since `origin` before which it cannot trip, see `RecloserBuilder::warm_up(...)`.
+    warm_up: u64,
+    /// Minimum time spent in `Closed(_)` or `HalfOpen(_)` state before leaving it,
+    /// see `RecloserBuilder::cooldown(...)`.
+    cooldown: Option<u64>,
+    idle_reset: Option<u64>,
+    /// How long `HalfOpen(_)` state may last, and the state entered afterward.
+    half_open_ttl: Option<(u64, State)>,
+    /// Time since `origin` of the latest call recorded in `Closed(_)` state.
+    last_recorded: AtomicU64,
+    /// Lengths of the windows, picked up when their state is entered.
+    closed_len: AtomicUsize,
+    half_open_len: AtomicUsize,
+    /// Number of transitions between public states, indexed by `[from][to]`.
+    transitions: [[AtomicU64; 4]; 4],
+    origin: Instant,
+    state: AtomicU64,
+    closed_rb: Atomic<RingBuffer>,
+    /// Decides transitions out of `Closed(_)` state instead of `closed_rb` when set.
+    closed_time_window: Option<TimeWindow>,
+    failure_count: Option<u32>,
+    minimum_calls: Option<usize>,
+    half_open_rb: Atomic<RingBuffer>,
+}
+
+impl Recloser {
+    /// Returns a builder to create a customized `Recloser`.
+    pub fn custom() -> RecloserBuilder {
+        RecloserBuilder::new()
+    }
 }
 
-impl Recloser {
-    /// Returns a builder to create a customized `Recloser`.
-    pub fn custom() -> RecloserBuilder {
-        RecloserBuilder::new()
-    }
+impl<W: WaitStrategy, P> Recloser<W, P> {
+    /// Wraps a function that may fail, records the result as success or failure.
+    /// Uses the default predicate, `AnyError` unless configured otherwise, that
+    /// considers any `Err(_)` as a failure.
+    /// Based on the result, state transition may happen.
+    pub fn call<F, T, E>(&self, f: F) -> Result<T, Error<E>>
+    where
+        P: ErrorPredicate<E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.guarded_call(&self.predicate, f)
+    }
+
+    /// Wraps a function that may fail, the custom `predicate` will be used to
+    /// determine whether the result was a success or failure.
+    /// Based on the result, state transition may happen.
+    pub fn call_with<Q, F, T, E>(&self, predicate: Q, f: F) -> Result<T, Error<E>>
+    where
+        Q: ErrorPredicate<E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.guarded_call(&predicate, f)
+    }
+
+    /// Same as `call_with(...)`, but `failed` receives the whole result rather than
+    /// only the error, so that `Ok(_)` values carrying a failure can be recorded as such.
+    /// Returns `true` when the call failed.
+    pub fn call_result_with<C, F, T, E>(&self, failed: C, f: F) -> Result<T, Error<E>>
+    where
+        C: FnOnce(&Result<T, E>) -> bool,
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.guarded(f, |res, guard| self.on_result(failed(res), guard))
+    }
+
+    /// Same as `call_with(...)`, but the whole result is classified by `classifier`,
+    /// which can also ignore it or open the `Recloser` immediately. The time the call took
+    /// is given to `Classifier::classify_timed(...)`.
+    pub fn call_classified<C, F, T, E>(&self, classifier: C, f: F) -> Result<T, Error<E>>
+    where
+        C: Classifier<T, E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let elapsed = std::cell::Cell::new(Duration::ZERO);
+        let timed = || {
+            let start = self.now();
+            let res = f();
+            elapsed.set(Duration::from_nanos(self.now().saturating_sub(start)));
+            res
+        };
+        self.guarded(timed, |res, guard| {
+            self.on_timed(
+                classifier.classify_timed(res, elapsed.get()),
+                elapsed.get(),
+                guard,
+            )
+        })
+    }
+
+    fn guarded_call<Q, F, T, E>(&self, predicate: &Q, f: F) -> Result<T, Error<E>>
+    where
+        Q: ErrorPredicate<E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        self.guarded(f, |res, guard| match res {
+            Ok(_) => self.on_result(false, guard),
+            Err(err) => self.on_err(predicate, err, guard),
+        })
+    }
+
+    pub(crate) fn guarded<F, C, T, E>(&self, f: F, classify: C) -> Result<T, Error<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+        C: FnOnce(&Result<T, E>, &Guard) -> CallOutcome,
+    {
+        let guard = &epoch::pin();
+        #[cfg(feature = "tracing")]
+        let span = match &self.trace_calls {
+            Some(sampler) if sampler.sample(self.now()) => {
+                Some((self.call_span().entered(), self.now()))
+            }
+            _ => None,
+        };
+
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+        let (res, outcome) = match self.permit(guard) {
+            None => (Err(Error::Rejected), CallOutcome::Rejected),
+            Some(_permit) => {
+                let _in_flight = self.counters.start();
+                let res = f();
+                let outcome = classify(&res, guard);
+                (res.map_err(Error::Inner), outcome)
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        if let Some((span, start)) = span {
+            let latency = Duration::from_nanos(self.now().saturating_sub(start));
+            span.record("outcome", tracing::field::debug(outcome));
+            span.record("latency", latency.as_secs_f64());
+        }
+        res
+    }
+
+    /// Returns whether a call is permitted, for callers that cannot hold a `Permit` until
+    /// the call completed. The slot it took in `HalfOpen(_)` state, if any, is freed by
+    /// `release_detached()` once its outcome is recorded.
+    #[cfg(any(feature = "ffi", feature = "pyo3", test))]
+    pub(crate) fn call_permitted(&self, guard: &Guard) -> bool {
+        match self.permit(guard) {
+            Some(permit) => {
+                if let Some(generation) = permit.into_slot() {
+                    self.probes.detach(generation);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a `Permit` if a call is permitted, to be held until the call completed.
+    pub(crate) fn permit(&self, guard: &Guard) -> Option<Permit<'_>> {
+        let permit = self.permitted(guard);
+        if permit.is_none() {
+            self.record_call(CallOutcome::Rejected);
+        }
+        permit
+    }
+
+    /// Frees the slot of a call permitted by `call_permitted(...)`, if any.
+    #[cfg(any(feature = "ffi", feature = "pyo3", test))]
+    pub(crate) fn release_detached(&self) {
+        self.probes.release_detached();
+    }
+
+    fn record_call(&self, outcome: CallOutcome) {
+        match outcome {
+            CallOutcome::Success => self.counters.success(self.now()),
+            CallOutcome::Failure => self.counters.failure(self.now()),
+            CallOutcome::Rejected => self.counters.rejection(),
+            CallOutcome::Ignored => (),
+        }
+        if let Some(hook) = &self.on_call {
+            hook.call(&outcome);
+        }
+    }
+
+    fn permitted(&self, guard: &Guard) -> Option<Permit<'_>> {
+        let current = self.state.load(Acquire);
+        let permitted = match unpack(current) {
+            (State::Closed, _) => true,
+            (State::HalfOpen, since) => {
+                if let Some((ttl, state)) = self.half_open_ttl {
+                    if self.now() > since.saturating_add(ttl)
+                        && (self.expire_half_open(current, state, guard)
+                            || self.state.load(Acquire) != current)
+                    {
+                        // Left `HalfOpen(_)` state, possibly through another transition
+                        return self.permitted(guard);
+                    }
+                }
+                return self.probes.acquire();
+            }
+            (State::Open, until) => {
+                let now = self.now();
+                if now <= until {
+                    return None;
+                }
+                match self.suspend_probe {
+                    // A delay too large to compute is never considered late
+                    Some(factor)
+                        if self
+                            .current_wait()
+                            .checked_mul(factor)
+                            .is_some_and(|late| now > until + nanos(late)) =>
+                    {
+                        self.transition(
+                            current,
+                            State::Probe,
+                            now,
+                            TransitionCause::OpenWait,
+                            guard,
+                        )
+                    }
+                    _ => {
+                        self.transition(
+                            current,
+                            State::HalfOpen,
+                            now,
+                            TransitionCause::OpenWait,
+                            guard,
+                        );
+                        return self.probes.acquire();
+                    }
+                }
+            }
+            (State::Probe, since) => {
+                // Allows another probe when the previous one never completed
+                let now = self.now();
+                now > since + nanos(self.current_wait())
+                    && self.transition(current, State::Probe, now, TransitionCause::OpenWait, guard)
+            }
+            (State::ForcedOpen, _) => false,
+        };
+        permitted.then_some(Permit { slot: None })
+    }
+
+    /// Leaves `HalfOpen(_)` state for `state` once its ttl elapsed, returns whether it
+    /// transitioned.
+    fn expire_half_open(&self, current: u64, state: State, guard: &Guard) -> bool {
+        let now = self.now();
+        let time = match state {
+            State::Closed => now,
+            _ => now + nanos(self.current_wait()),
+        };
+        self.transition(current, state, time, TransitionCause::Expired, guard)
+    }
+
+    /// Returns the remaining time to wait before a call will be permitted again,
+    /// or `None` when not in `Open(_)` state. When latched in `ForcedOpen` state,
+    /// the current open wait is returned.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn open_remaining(&self, _guard: &Guard) -> Option<Duration> {
+        match unpack(self.state.load(Acquire)) {
+            (State::Open, until) => Some(Duration::from_nanos(until.saturating_sub(self.now()))),
+            (State::ForcedOpen, _) => Some(self.current_wait()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn on_success(&self, guard: &Guard) {
+        self.record_call(CallOutcome::Success);
+        let current = self.state.load(Acquire);
+        let (state, _) = unpack(current);
+        self.record_slo(state, false);
+
+        match state {
+            State::Closed => {
+                self.clear_idle(guard);
+                self.consecutive_failures.store(0, Relaxed);
+                let failure_rate = self.closed_rate(current, 0.0, guard);
+                if failure_rate > -1.0 {
+                    self.closed_tripped(failure_rate);
+                }
+                for category in &self.categories {
+                    category.record(0.0);
+                }
+            }
+            State::HalfOpen => {
+                let failure_rate = self.half_open_rate(current, 0.0, guard);
+                let successes = self.successes.fetch_add(1, Relaxed) + 1;
+                match self.half_open_successes {
+                    Some(required) if successes >= required => {
+                        self.transition(
+                            current,
+                            State::Closed,
+                            self.now(),
+                            TransitionCause::FailureRate,
+                            guard,
+                        );
+                    }
+                    _ if failure_rate > -1.0 => {
+                        self.half_open_decide(current, failure_rate, false, None, guard);
+                    }
+                    _ => (),
+                }
+            }
+            State::Probe => {
+                self.transition(
+                    current,
+                    State::HalfOpen,
+                    self.now(),
+                    TransitionCause::FailureRate,
+                    guard,
+                );
+            }
+            State::Open | State::ForcedOpen => (),
+        };
+    }
+
+    pub(crate) fn on_error(&self, guard: &Guard) {
+        self.failed(1.0, None, guard);
+    }
+
+    /// Records a failure weighing `weight` in the failure_rate, and in its `category`
+    /// if any, returns whether it tripped the `Recloser`.
+    pub(crate) fn failed(
+        &self,
+        weight: f32,
+        category: Option<&'static str>,
+        guard: &Guard,
+    ) -> bool {
+        self.record_call(CallOutcome::Failure);
+        let current = self.state.load(Acquire);
+        let (state, _) = unpack(current);
+        self.record_slo(state, true);
+
+        match state {
+            State::Closed => {
+                self.clear_idle(guard);
+                let mut tripped_by = None;
+                for c in &self.categories {
+                    let weight = if category == Some(c.name) {
+                        weight
+                    } else {
+                        0.0
+                    };
+                    if c.record(weight) && tripped_by.is_none() {
+                        tripped_by = Some(TransitionCause::Category(c.name));
+                    }
+                }
+                let failure_rate = self.closed_rate(current, weight, guard);
+                let consecutive_failures = self.consecutive_failures.fetch_add(1, Relaxed) + 1;
+                let cause = if let Some(clause) =
+                    self.policy_fired(failure_rate, consecutive_failures, guard)
+                {
+                    Some(TransitionCause::Policy(clause))
+                } else if (failure_rate > -1.0 && self.closed_tripped(failure_rate))
+                    || self.failure_count_reached(guard)
+                {
+                    Some(TransitionCause::FailureRate)
+                } else {
+                    tripped_by
+                };
+                match cause {
+                    Some(cause) => {
+                        let ctx = WaitContext::new(0).with_category(category);
+                        let wait = self.wait.open_wait(&ctx);
+                        let tripped = self.transition(
+                            current,
+                            State::Open,
+                            self.now() + nanos(wait),
+                            cause,
+                            guard,
+                        );
+                        if tripped {
+                            *self.open_category.lock().unwrap_or_else(|e| e.into_inner()) =
+                                category;
+                        }
+                        tripped
+                    }
+                    None => false,
+                }
+            }
+            State::HalfOpen => {
+                self.successes.store(0, Relaxed);
+                let failure_rate = self.half_open_rate(current, weight, guard);
+                failure_rate > -1.0
+                    && self.half_open_decide(current, failure_rate, true, category, guard)
+            }
+            State::Probe => self.reopen(current, category, guard),
+            State::Open | State::ForcedOpen => false,
+        }
+    }
+
+    pub(crate) fn on_result(&self, failed: bool, guard: &Guard) -> CallOutcome {
+        if failed {
+            self.on_error(guard);
+            CallOutcome::Failure
+        } else {
+            self.on_success(guard);
+            CallOutcome::Success
+        }
+    }
+
+    pub(crate) fn on_classified(&self, class: Outcome, guard: &Guard) -> CallOutcome {
+        match class {
+            Outcome::Success => self.on_result(false, guard),
+            Outcome::Failure => self.on_result(true, guard),
+            Outcome::Ignore => {
+                self.record_call(CallOutcome::Ignored);
+                CallOutcome::Ignored
+            }
+            Outcome::Fatal => {
+                self.on_fatal(guard);
+                CallOutcome::Failure
+            }
+        }
+    }
+
+    /// Same as `on_classified(...)` for a call that took `elapsed`, which is recorded for
+    /// `TripPolicy::slow_call_rate(...)` in `Closed(_)` state.
+    pub(crate) fn on_timed(&self, class: Outcome, elapsed: Duration, guard: &Guard) -> CallOutcome {
+        if let Some(slow_calls) = &self.slow_calls {
+            let (state, _) = unpack(self.state.load(Acquire));
+            if state == State::Closed && class != Outcome::Ignore {
+                slow_calls.record(elapsed);
+            }
+        }
+        self.on_classified(class, guard)
+    }
+
+    /// Records an `Err(_)` as classified by `predicate`, returns how it was recorded.
+    pub(crate) fn on_err<Q, E>(&self, predicate: &Q, err: &E, guard: &Guard) -> CallOutcome
+    where
+        Q: ErrorPredicate<E>,
+    {
+        let tripped = if predicate.is_ignored(err) {
+            return self.on_classified(Outcome::Ignore, guard);
+        } else if predicate.is_fuse(err) {
+            self.on_fuse(guard)
+        } else if predicate.is_err(err) {
+            self.failed(predicate.weight(err), predicate.category(err), guard)
+        } else {
+            self.on_success(guard);
+            return CallOutcome::Success;
+        };
+        if tripped {
+            self.keep_trip_error(predicate.describe(err));
+        }
+        CallOutcome::Failure
+    }
+
+    /// Keeps the `description` of the error that tripped the `Recloser`, if any.
+    pub(crate) fn keep_trip_error(&self, description: Option<String>) {
+        if let Some(description) = description {
+            *self.trip_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(description);
+        }
+    }
+
+    /// Returns the description of the error that most recently tripped the `Recloser`,
+    /// as given by `ErrorPredicate::describe(...)`, for instance with `describe_with(...)`.
+    /// It is kept once the `Recloser` closed again, until it trips anew.
+    pub fn trip_error(&self) -> Option<String> {
+        self.trip_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Opens for the usual open wait without recording into the windows, returns
+    /// whether it tripped the `Recloser`.
+    fn on_fatal(&self, guard: &Guard) -> bool {
+        self.record_call(CallOutcome::Failure);
+        if self.trip_suppressed() {
+            return false;
+        }
+        loop {
+            let current = self.state.load(Acquire);
+            let (state, _) = unpack(current);
+            if matches!(state, State::Open | State::ForcedOpen) {
+                return false;
+            }
+
+            let wait = self
+                .wait
+                .open_wait(&WaitContext::new(self.reopens.load(Relaxed)));
+            let until = self.now() + nanos(wait);
+            if self.transition(current, State::Open, until, TransitionCause::Fatal, guard) {
+                *self.open_category.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                self.record_slo(state, true);
+                return true;
+            }
+        }
+    }
+
+    /// Transitions to `Open(_)` state for `fuse_wait`, unless it is latched in `ForcedOpen`
+    /// state or already `Open(_)` for longer. Returns whether it transitioned.
+    pub(crate) fn on_fuse(&self, guard: &Guard) -> bool {
+        self.record_call(CallOutcome::Failure);
+        if self.trip_suppressed() {
+            return false;
+        }
+        loop {
+            let current = self.state.load(Acquire);
+            let (state, time) = unpack(current);
+
+            let until = self.now() + nanos(self.fuse_wait);
+            let blown = match state {
+                State::ForcedOpen => return false,
+                State::Open if time >= until => return false,
+                _ => self.transition(current, State::Open, until, TransitionCause::Fuse, guard),
+            };
+            if blown {
+                self.record_slo(state, true);
+                return true;
+            }
+        }
+    }
+
+    /// Returns why calls are currently rejected, `None` when they are permitted.
+    pub fn rejection(&self) -> Option<Rejection> {
+        let now = self.now();
+        let (state, retry_after) = match unpack(self.state.load(Acquire)) {
+            (State::Open, until) if now <= until => (State::Open, Some(until - now)),
+            (State::Probe, since) => {
+                let until = since + nanos(self.current_wait());
+                if now > until {
+                    return None;
+                }
+                (State::Probe, Some(until - now))
+            }
+            (State::ForcedOpen, _) => (State::ForcedOpen, None),
+            _ => return None,
+        };
+        Some(Rejection {
+            state: state.public(),
+            retry_after: retry_after.map(Duration::from_nanos),
+        })
+    }
+
+    /// Returns the time until the next call is permitted and transitions to `HalfOpen(_)`
+    /// state, `Duration::ZERO` once it is due. Returns `None` when not in `Open(_)`
+    /// state, in particular when latched in `ForcedOpen` state.
+    pub fn time_until_half_open(&self) -> Option<Duration> {
+        match unpack(self.state.load(Acquire)) {
+            (State::Open, until) => Some(Duration::from_nanos(until.saturating_sub(self.now()))),
+            _ => None,
+        }
+    }
+
+    /// Returns a point in time view of the `Recloser`, typically to be reported as metrics.
+    pub fn snapshot(&self) -> Snapshot {
+        let guard = &epoch::pin();
+        let (state, _) = unpack(self.state.load(Acquire));
+        let failure_rate = match state {
+            State::Closed => self.closed_stats(guard).0,
+            State::HalfOpen => self.half_open_rb(guard).failure_rate(),
+            State::Open | State::Probe | State::ForcedOpen => None,
+        };
+        Snapshot {
+            state: state.public(),
+            failure_rate,
+            retry_after: self.rejection().and_then(|rejection| rejection.retry_after),
+            threshold: self.threshold(),
+            metrics: self.counters.metrics(failure_rate),
+            flaps: [CircuitState::Closed, CircuitState::HalfOpen]
+                .into_iter()
+                .map(|from| self.transitions(from, CircuitState::Open))
+                .sum(),
+            taken_at: SystemTime::now(),
+        }
+    }
+
+    /// Returns a `CancellationToken` that will be cancelled on the next transition
+    /// to `Open(_)` state, so that in-flight calls may be aborted early.
+    #[cfg(feature = "tokio")]
+    pub fn cancellation_token(&self) -> tokio_util::sync::CancellationToken {
+        self.cancel
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(Default::default)
+            .clone()
+    }
+
+    /// Leaves `ForcedOpen` state by transitioning to `Closed(_)` state,
+    /// returns `false` if the `Recloser` was not in `ForcedOpen` state.
+    pub fn force_close(&self) -> bool {
+        let guard = &epoch::pin();
+        let current = self.state.load(Acquire);
+        match unpack(current) {
+            (State::ForcedOpen, _) => self.transition(
+                current,
+                State::Closed,
+                self.now(),
+                TransitionCause::Manual,
+                guard,
+            ),
+            _ => false,
+        }
+    }
+
+    /// Transitions to `ForcedOpen` state from any state, all calls are then rejected
+    /// until `force_close()` or `reset()` is called.
+    pub fn force_open(&self) {
+        self.force(State::ForcedOpen, |now| now);
+    }
+
+    /// Transitions to `Closed(_)` state from any state, with a fresh `RingBuffer`.
+    pub fn reset(&self) {
+        self.force(State::Closed, |now| now);
+    }
+
+    /// Transitions to `Open(_)` state from any state, for the duration determined by
+    /// the `WaitStrategy` as if it tripped.
+    pub fn trip(&self) {
+        *self.open_category.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let wait = self.current_wait();
+        self.force(State::Open, |now| now + nanos(wait));
+    }
+
+    /// Transitions to `Open(_)` state from any state, for the given `duration`.
+    pub fn open_for(&self, duration: Duration) {
+        self.force(State::Open, |now| now + nanos(duration));
+    }
+
+    /// Disables the `Recloser`, or enables it back: while disabled, calls are recorded
+    /// as usual but it never trips on its own, so that a new breaker can be rolled out in
+    /// shadow mode before being enforced. Disabling transitions to `Closed(_)` state so
+    /// that all calls are permitted, manual transitions such as `trip()` remain effective.
+    pub fn set_disabled(&self, disabled: bool) {
+        self.disabled.store(disabled, Relaxed);
+        if disabled && self.state() != CircuitState::Closed {
+            self.reset();
+        }
+    }
+
+    /// Returns whether the `Recloser` is disabled, see `set_disabled(...)`.
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Relaxed)
+    }
+
+    /// Whether it cannot trip on its own, because it is disabled or still warming up.
+    fn trip_suppressed(&self) -> bool {
+        self.is_disabled() || self.now() < self.warm_up
+    }
+
+    /// Whether the `current` state was entered too recently to be left because of `cause`,
+    /// transitions that are manual or driven by time are never held back.
+    fn cooling_down(&self, current: u64, cause: TransitionCause) -> bool {
+        let Some(cooldown) = self.cooldown else {
+            return false;
+        };
+        match (unpack(current), cause) {
+            (
+                _,
+                TransitionCause::Manual
+                | TransitionCause::Fuse
+                | TransitionCause::Fatal
+                | TransitionCause::Expired
+                | TransitionCause::OpenWait,
+            ) => false,
+            ((State::Closed | State::HalfOpen, since), _) => {
+                self.now() < since.saturating_add(cooldown)
+            }
+            _ => false,
+        }
+    }
+
+    fn force(&self, state: State, time: impl Fn(u64) -> u64) {
+        let guard = &epoch::pin();
+        loop {
+            let current = self.state.load(Acquire);
+            if unpack(current).0 == state && state == State::ForcedOpen {
+                return;
+            }
+            if self.transition(
+                current,
+                state,
+                time(self.now()),
+                TransitionCause::Manual,
+                guard,
+            ) {
+                return;
+            }
+        }
+    }
+
+    /// Returns the name given with `RecloserBuilder::name(...)`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the current state, for instance to tell `HalfOpen(_)` from `Closed(_)`
+    /// state in monitoring code.
+    pub fn state(&self) -> CircuitState {
+        unpack(self.state.load(Acquire)).0.public()
+    }
+
+    /// Returns the number of transitions that happened from `from` to `to` state.
+    pub(crate) fn transitions(&self, from: CircuitState, to: CircuitState) -> u64 {
+        self.transitions[from as usize][to as usize].load(Relaxed)
+    }
+
+    pub(crate) fn reset_transitions(&self) {
+        for count in self.transitions.iter().flatten() {
+            count.store(0, Relaxed);
+        }
+    }
+
+    /// Returns the length of the `Closed(_)` and `HalfOpen(_)` windows.
+    pub(crate) fn windows(&self) -> (usize, usize) {
+        let guard = &epoch::pin();
+        (self.closed_rb(guard).len(), self.half_open_rb(guard).len())
+    }
+
+    /// Transitions from the `current` packed state to `state`, entered at or until
+    /// `time` (in nanoseconds since `origin`), because of `cause`. Returns `false` if
+    /// another thread performed a transition in the meantime.
+    fn transition(
+        &self,
+        current: u64,
+        state: State,
+        time: u64,
+        cause: TransitionCause,
+        guard: &Guard,
+    ) -> bool {
+        let tripping = matches!(state, State::Open | State::ForcedOpen);
+        if tripping && cause != TransitionCause::Manual && self.trip_suppressed() {
+            return false;
+        }
+        if self.cooling_down(current, cause) {
+            return false;
+        }
+        if self
+            .state
+            .compare_exchange(current, pack(state, time), AcqRel, Acquire)
+            .is_err()
+        {
+            return false;
+        }
+
+        let (from, to) = (unpack(current).0.public(), state.public());
+        if from != to {
+            self.transitions[from as usize][to as usize].fetch_add(1, Relaxed);
+            let elapsed = self.uptime.leave(from, self.now());
+            self.listeners.notify(from, to);
+            if cfg!(feature = "tracing") || self.history.is_some() || !self.subscribers.is_empty() {
+                let (failure_rate, calls) = match unpack(current).0 {
+                    State::Closed => self.closed_stats(guard),
+                    State::HalfOpen => window_stats(self.half_open_rb(guard)),
+                    State::Open | State::Probe | State::ForcedOpen => (None, 0),
+                };
+                let event = TransitionEvent {
+                    from,
+                    to,
+                    at: SystemTime::now(),
+                    cause,
+                    failure_rate,
+                    calls,
+                    elapsed,
+                };
+                #[cfg(feature = "tracing")]
+                self.trace_transition(&event);
+                if let Some(history) = &self.history {
+                    history.push(event);
+                }
+                self.subscribers.publish(event);
+            }
+            #[cfg(feature = "tokio")]
+            self.watch.send_replace(to);
+        }
+
+        match state {
+            State::Closed => {
+                self.window(&self.closed_rb, &self.closed_len, guard)
+                    .renew(pack(state, time));
+                if let Some(time_window) = &self.closed_time_window {
+                    time_window.reset();
+                }
+                for category in &self.categories {
+                    category.reset();
+                }
+                if let Some(rate_jump) = &self.rate_jump {
+                    rate_jump.reset();
+                }
+                if let Some(slow_calls) = &self.slow_calls {
+                    slow_calls.reset();
+                }
+                if let Some(warner) = &self.warner {
+                    warner.rearm();
+                }
+                self.consecutive_failures.store(0, Relaxed);
+                self.reopens.store(0, Relaxed);
+            }
+            State::HalfOpen => {
+                self.window(&self.half_open_rb, &self.half_open_len, guard)
+                    .renew(pack(state, time));
+                self.successes.store(0, Relaxed);
+                self.probes.renew();
+            }
+            #[cfg(feature = "tokio")]
+            State::Open | State::ForcedOpen => {
+                if let Some(token) = self.cancel.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                    token.cancel();
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// The span wrapping a call when `RecloserBuilder::trace_calls()` is set, its
+    /// `outcome` and `latency` are recorded once the call completed.
+    #[cfg(feature = "tracing")]
+    fn call_span(&self) -> tracing::Span {
+        tracing::info_span!(
+            "recloser_call",
+            breaker_name = self.name(),
+            state = ?self.state(),
+            outcome = tracing::field::Empty,
+            latency = tracing::field::Empty,
+        )
+    }
+
+    /// Emits a single `tracing` event per transition, at `WARN` level when it trips.
+    #[cfg(feature = "tracing")]
+    fn trace_transition(&self, event: &TransitionEvent) {
+        let (from, to, cause) = (event.from, event.to, event.cause);
+        let (failure_rate, calls) = (event.failure_rate, event.calls);
+        let breaker_name = self.name();
+        let duration_in_previous_state = event.elapsed.as_secs_f64();
+        macro_rules! transition {
+            ($level:ident, $message:literal) => {
+                tracing::$level!(
+                    breaker_name,
+                    ?from,
+                    ?to,
+                    ?cause,
+                    failure_rate,
+                    calls,
+                    duration_in_previous_state,
+                    $message
+                )
+            };
+        }
+        match to {
+            CircuitState::Open => transition!(warn, "recloser tripped"),
+            _ => transition!(info, "recloser transitioned"),
+        }
+    }
+
+    /// Decides the transition out of `HalfOpen(_)` state, once per window: closes after
+    /// a success when the `failure_rate` is at most the threshold, reopens after a failure,
+    /// of `category` if any, when it is at least the threshold, and stays otherwise. With
+    /// `half_open_successes(...)` it only ever reopens.
+    fn half_open_decide(
+        &self,
+        current: u64,
+        failure_rate: f32,
+        failed: bool,
+        category: Option<&'static str>,
+        guard: &Guard,
+    ) -> bool {
+        let threshold = self.half_open_threshold();
+        match failed {
+            false if failure_rate <= threshold => {
+                if self.half_open_successes.is_none() {
+                    self.transition(
+                        current,
+                        State::Closed,
+                        self.now(),
+                        TransitionCause::FailureRate,
+                        guard,
+                    );
+                }
+                false
+            }
+            true if failure_rate >= threshold => self.reopen(current, category, guard),
+            _ => false,
+        }
+    }
+
+    /// Records a call in the `current` `Closed(_)` state, failed when `weight` is not zero,
+    /// returns the failure_rate deciding whether to trip or `-1.0`.
+    fn closed_rate(&self, current: u64, weight: f32, guard: &Guard) -> f32 {
+        let rb = self.closed_rb(guard);
+        if let Some(time_window) = &self.closed_time_window {
+            let min_calls = self.minimum_calls.unwrap_or(rb.len());
+            return time_window.set_weighted(self.now(), weight, min_calls);
+        }
+        // Dropped when recorded late, after the state was left
+        let Some(failure_rate) = rb.set_weighted_for(current, weight, None) else {
+            return -1.0;
+        };
+        match self.minimum_calls {
+            Some(min_calls)
+                if failure_rate == -1.0 && rb.calls() >= min_calls.clamp(1, rb.len()) =>
+            {
+                rb.failure_rate().unwrap_or(-1.0)
+            }
+            _ => failure_rate,
+        }
+    }
+
+    /// Whether the `Closed(_)` window holds at least `failure_count` failures, and at
+    /// least `minimum_calls` calls.
+    fn failure_count_reached(&self, guard: &Guard) -> bool {
+        let Some(count) = self.failure_count else {
+            return false;
+        };
+        if let Some(min_calls) = self.minimum_calls {
+            if self.closed_stats(guard).1 < min_calls {
+                return false;
+            }
+        }
+        self.closed_failures(guard) >= count as f32
+    }
+
+    /// The name of the clause of the `trip_policy` that fired after a failed call, if
+    /// any, once the `Closed(_)` window holds at least `minimum_calls` calls.
+    fn policy_fired(
+        &self,
+        failure_rate: f32,
+        consecutive_failures: u32,
+        guard: &Guard,
+    ) -> Option<&'static str> {
+        let policy = self.trip_policy.as_ref()?;
+        if let Some(min_calls) = self.minimum_calls {
+            if self.closed_stats(guard).1 < min_calls {
+                return None;
+            }
+        }
+        policy.fired(&Observed {
+            failure_rate,
+            failures: self.closed_failures(guard),
+            consecutive_failures,
+            slow_calls: self.slow_calls.as_ref(),
+        })
+    }
+
+    /// The weighted number of failures of the `Closed(_)` window.
+    fn closed_failures(&self, guard: &Guard) -> f32 {
+        match &self.closed_time_window {
+            Some(time_window) => time_window.failures(self.now()),
+            None => self.closed_rb(guard).failures(),
+        }
+    }
+
+    /// The failure_rate of the `Closed(_)` window along with its number of calls.
+    fn closed_stats(&self, guard: &Guard) -> (Option<f32>, usize) {
+        match &self.closed_time_window {
+            Some(time_window) => time_window.stats(self.now()),
+            None => window_stats(self.closed_rb(guard)),
+        }
+    }
+
+    /// Records a call in the `current` `HalfOpen(_)` state, failed when `weight` is not
+    /// zero, returns the failure_rate once every `half_open_min_calls` calls or `-1.0`.
+    fn half_open_rate(&self, current: u64, weight: f32, guard: &Guard) -> f32 {
+        let rb = self.half_open_rb(guard);
+        let every = self
+            .half_open_min_calls
+            .unwrap_or(rb.len())
+            .clamp(1, rb.len().max(1));
+        rb.set_weighted_for(current, weight, Some(every))
+            .unwrap_or(-1.0)
+    }
+
+    /// Transitions to `Open(_)` state again, without having been `Closed(_)` in between,
+    /// because of a failed call of `category` if any.
+    fn reopen(&self, current: u64, category: Option<&'static str>, guard: &Guard) -> bool {
+        let reopens = self.reopens.load(Relaxed) + 1;
+        let escalation = self
+            .escalation
+            .as_ref()
+            .filter(|escalation| escalation.reopens == reopens);
+        let flapping = self
+            .flap_latch
+            .as_ref()
+            .filter(|(flaps, window)| window.totals(self.now()).0 + 1 >= *flaps);
+
+        let transitioned = match (flapping, escalation) {
+            (Some(_), _) => self.transition(
+                current,
+                State::ForcedOpen,
+                self.now(),
+                TransitionCause::Flapping,
+                guard,
+            ),
+            (None, Some(escalation)) if escalation.forced_open => self.transition(
+                current,
+                State::ForcedOpen,
+                self.now(),
+                TransitionCause::Escalation,
+                guard,
+            ),
+            _ => {
+                let ctx = WaitContext::new(reopens).with_category(category);
+                let wait = self.wait.open_wait(&ctx);
+                self.transition(
+                    current,
+                    State::Open,
+                    self.now() + nanos(wait),
+                    TransitionCause::FailureRate,
+                    guard,
+                )
+            }
+        };
+
+        if transitioned {
+            self.reopens.store(reopens, Relaxed);
+            *self.open_category.lock().unwrap_or_else(|e| e.into_inner()) = category;
+            if let Some(escalation) = escalation {
+                escalation.hook.call(&Escalation { reopens });
+            }
+            match (&self.flap_latch, flapping) {
+                // Counts anew once manually closed
+                (_, Some((_, window))) => window.reset(),
+                (Some((_, window)), None) => window.record(self.now(), false),
+                (None, None) => (),
+            }
+        }
+        transitioned
+    }
+
+    /// The open wait of the current, or latest, `Open(_)` state.
+    pub(crate) fn current_wait(&self) -> Duration {
+        let reopens = self.reopens.load(Relaxed);
+        let category = *self.open_category.lock().unwrap_or_else(|e| e.into_inner());
+        self.wait
+            .open_wait(&WaitContext::new(reopens).with_category(category))
+    }
+
+    /// Returns the `RingBuffer` of a window, replaced by an empty one when its length
+    /// was changed with `set_window_lens(...)` since it was allocated.
+    fn window<'g>(
+        &self,
+        rb: &Atomic<RingBuffer>,
+        len: &AtomicUsize,
+        guard: &'g Guard,
+    ) -> &'g RingBuffer {
+        let len = len.load(Relaxed);
+        // Safety: safe because `Shared::null()` is never used.
+        let current = unsafe { rb.load(Acquire, guard).deref() };
+        if current.len() == len {
+            return current;
+        }
+        let resized = Owned::new(current.with_len(len)).into_shared(guard);
+        let replaced = rb.swap(resized, AcqRel, guard);
+        // Safety: safe because the replaced `RingBuffer` is only destroyed once no
+        // thread can still be reading it, and `resized` is never null.
+        unsafe {
+            guard.defer_destroy(replaced);
+            resized.deref()
+        }
+    }
+
+    /// Changes the lengths of the `Closed(_)` and `HalfOpen(_)` windows, each one is
+    /// picked up the next time its state is entered. Empty windows hold a single call.
+    pub(crate) fn set_window_lens(&self, closed_len: Option<usize>, half_open_len: Option<usize>) {
+        if let Some(len) = closed_len {
+            self.closed_len.store(len.max(1), Relaxed);
+        }
+        if let Some(len) = half_open_len {
+            self.half_open_len.store(len.max(1), Relaxed);
+        }
+    }
+
+    fn closed_rb<'g>(&self, guard: &'g Guard) -> &'g RingBuffer {
+        // Safety: safe because `Shared::null()` is never used.
+        unsafe { self.closed_rb.load(Acquire, guard).deref() }
+    }
+
+    fn half_open_rb<'g>(&self, guard: &'g Guard) -> &'g RingBuffer {
+        // Safety: safe because `Shared::null()` is never used.
+        unsafe { self.half_open_rb.load(Acquire, guard).deref() }
+    }
+
+    /// Returns the number of nanoseconds elapsed since `origin`.
+    pub(crate) fn now(&self) -> u64 {
+        let now = Instant::now();
+        let elapsed = if now > self.origin {
+            nanos(now - self.origin)
+        } else {
+            0
+        };
+        #[cfg(any(feature = "trace", feature = "proptest"))]
+        let elapsed = elapsed.saturating_add(self.skew.load(Relaxed));
+        elapsed
+    }
+
+    /// Moves the clock forward so that at least `at` has elapsed since `origin`.
+    #[cfg(feature = "trace")]
+    pub(crate) fn skew_to(&self, at: Duration) {
+        let (at, now) = (nanos(at), self.now());
+        if at > now {
+            self.skew.fetch_add(at - now, Relaxed);
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn advance(&self, duration: Duration) {
+        self.skew.fetch_add(nanos(duration), Relaxed);
+    }
+
+    #[cfg(test)]
+    fn current_state(&self) -> State {
+        unpack(self.state.load(Acquire)).0
+    }
+
+    /// Clears the `Closed(_)` windows when no call was recorded for longer than
+    /// `idle_reset`, before recording a new one.
+    fn clear_idle(&self, guard: &Guard) {
+        if let Some(idle_reset) = self.idle_reset {
+            let now = self.now();
+            if now.saturating_sub(self.last_recorded.swap(now, Relaxed)) > idle_reset {
+                self.closed_rb(guard).reset();
+                if let Some(time_window) = &self.closed_time_window {
+                    time_window.reset();
+                }
+                for category in &self.categories {
+                    category.reset();
+                }
+            }
+        }
+    }
+
+    /// Records a `failure_rate` calculated in `Closed(_)` state, returns whether
+    /// it should trip.
+    fn closed_tripped(&self, failure_rate: f32) -> bool {
+        let threshold = match &self.anomaly {
+            Some(anomaly) => anomaly
+                .observe(self.now(), failure_rate)
+                .unwrap_or(self.threshold()),
+            None => self.threshold(),
+        };
+        let jumped = match &self.rate_jump {
+            Some(rate_jump) => rate_jump.observe(self.now(), failure_rate),
+            None => false,
+        };
+        let burning = match &self.slo {
+            Some(slo) => slo.burning(self.now()),
+            None => false,
+        };
+        let reached = self.trip_policy.is_none() && failure_rate >= threshold;
+        let tripped = reached || jumped || burning;
+        if let (false, Some(warner)) = (tripped, &self.warner) {
+            warner.observe(failure_rate, threshold);
+        }
+        tripped
+    }
+
+    fn record_slo(&self, state: State, failed: bool) {
+        if let (Some(slo), State::Closed | State::HalfOpen) = (&self.slo, state) {
+            slo.record(self.now(), failed);
+        }
+    }
+
+    /// The threshold used to decide transitions from `HalfOpen(_)` state.
+    pub(crate) fn half_open_threshold(&self) -> f32 {
+        self.anomaly
+            .as_ref()
+            .and_then(|anomaly| anomaly.threshold())
+            .unwrap_or(self.threshold())
+    }
+
+    pub(crate) fn threshold(&self) -> f32 {
+        f32::from_bits(self.threshold.load(Relaxed))
+    }
+
+    pub(crate) fn set_threshold(&self, threshold: f32) {
+        self.threshold.store(threshold.to_bits(), Relaxed);
+    }
+}
+
+impl<W, P> Recloser<W, P> {
+    /// Returns whether all the `half_open_max_calls(...)` slots are taken.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn probes_full(&self) -> bool {
+        self.probes.full()
+    }
+
+    /// Frees the slot kept by `Permit::into_slot()`.
+    pub(crate) fn release_slot(&self, generation: u64) {
+        self.probes.release(generation);
+    }
+}
+
+impl<W, P> Drop for Recloser<W, P> {
+    fn drop(&mut self) {
+        // Safety: safe because the `Recloser` is not shared anymore.
+        unsafe {
+            let guard = epoch::unprotected();
+            drop(self.closed_rb.load(Relaxed, guard).into_owned());
+            drop(self.half_open_rb.load(Relaxed, guard).into_owned());
+        }
+    }
+}
+
+/// The states a `Recloser` can be in.
+///
+/// The current state is packed along with a time in a single `AtomicU64`, so that
+/// transitions never allocate. The `RingBuffer`s are allocated once and reset
+/// when their state is entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum State {
+    /// Allows calls until a failure_rate threshold is reached.
+    Closed = 0,
+    /// Rejects all calls until the packed time is reached.
+    Open = 1,
+    /// Allows calls until the underlying `RingBuffer` is full, then calculates a
+    /// failure_rate once per window based on which the next transition will happen.
+    HalfOpen = 2,
+    /// Entered instead of `HalfOpen(_)` when `Open(_)` lasted way longer than expected,
+    /// for instance after the host was suspended. Allows a single call, started at the
+    /// packed time, based on which transition to `HalfOpen(_)` or `Open(_)` will happen.
+    Probe = 3,
+    /// Rejects all calls until `force_close()` is called.
+    ForcedOpen = 4,
+}
+
+impl State {
+    fn public(self) -> CircuitState {
+        match self {
+            State::Closed => CircuitState::Closed,
+            State::Open => CircuitState::Open,
+            State::HalfOpen | State::Probe => CircuitState::HalfOpen,
+            State::ForcedOpen => CircuitState::ForcedOpen,
+        }
+    }
+}
+
+/// The state a `Recloser` is in, as observed from the outside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    /// Also covers the single call allowed by `suspend_probe(...)`.
+    HalfOpen,
+    ForcedOpen,
+}
+
+/// Describes why calls are currently rejected, see `Recloser::rejection()`.
+///
+/// With the `serde` feature, `retry_after` serializes as a number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct Rejection {
+    pub state: CircuitState,
+    /// Time until calls may be permitted again, `None` in `ForcedOpen` state.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_secs"))]
+    pub retry_after: Option<Duration>,
+}
+
+/// A point in time view of a `Recloser`, see `Recloser::snapshot()`.
+///
+/// With the `serde` feature, it can be dumped into debug endpoints, or persisted to be
+/// restored with `RecloserBuilder::restore(...)`. `retry_after` (de)serializes as a
+/// number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Snapshot {
+    pub state: CircuitState,
+    /// Failure rate of the calls recorded in the current state, `None` if there
+    /// are none or when calls are rejected.
+    pub failure_rate: Option<f32>,
+    /// Time until calls may be permitted again, `None` when they are permitted
+    /// or in `ForcedOpen` state.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_secs",
+            deserialize_with = "deserialize_secs"
+        )
+    )]
+    pub retry_after: Option<Duration>,
+    /// The current `error_rate`, as set on the builder or by a `Command::UpdateConfig`.
+    pub threshold: f32,
+    pub metrics: Metrics,
+    /// Number of times the failure_rate tripped to `Open(_)` state, since the
+    /// `Recloser` was built or since the last `reset_metrics()`.
+    pub flaps: u64,
+    /// When it was taken, so that `RecloserBuilder::restore(...)` accounts for the time
+    /// elapsed since.
+    pub taken_at: SystemTime,
+}
+
+#[cfg(feature = "serde")]
+fn serialize_secs<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_secs<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    use serde::{de::Error, Deserialize};
+
+    Option::<f64>::deserialize(deserializer)?
+        .map(|secs| Duration::try_from_secs_f64(secs).map_err(D::Error::custom))
+        .transpose()
+}
+
+/// Mask of the number of slots, packed with their generation in `Probes`.
+const SLOTS: u64 = u32::MAX as u64;
+
+/// The slots of the calls in progress in `HalfOpen(_)` state, at most `max_calls`.
+/// Slots are packed with the generation of the `HalfOpen(_)` state they were taken in,
+/// so that calls completing after it was left do not free the slots of a newer one.
+#[derive(Debug)]
+struct Probes {
+    max_calls: Option<u64>,
+    taken: AtomicU64,
+    /// Slots taken by `Recloser::call_permitted(...)` whose outcome is not recorded yet.
+    detached: AtomicU64,
+}
+
+impl Probes {
+    fn new(max_calls: Option<usize>) -> Self {
+        Probes {
+            max_calls: max_calls.map(|max_calls| max_calls as u64),
+            taken: AtomicU64::new(0),
+            detached: AtomicU64::new(0),
+        }
+    }
+
+    fn acquire(&self) -> Option<Permit<'_>> {
+        let Some(max_calls) = self.max_calls else {
+            return Some(Permit { slot: None });
+        };
+        let taken = self
+            .taken
+            .fetch_update(Relaxed, Relaxed, |taken| {
+                (taken & SLOTS < max_calls).then_some(taken + 1)
+            })
+            .ok()?;
+        Some(Permit {
+            slot: Some((self, taken >> 32)),
+        })
+    }
+
+    fn release(&self, generation: u64) {
+        let _ = self.taken.fetch_update(Relaxed, Relaxed, |taken| {
+            (taken >> 32 == generation && taken & SLOTS > 0).then(|| taken - 1)
+        });
+    }
+
+    #[cfg(any(feature = "ffi", feature = "pyo3", test))]
+    fn detach(&self, generation: u64) {
+        let _ = self.detached.fetch_update(Relaxed, Relaxed, |detached| {
+            (detached >> 32 == generation).then_some(detached + 1)
+        });
+    }
+
+    #[cfg(any(feature = "ffi", feature = "pyo3", test))]
+    fn release_detached(&self) {
+        if let Ok(detached) = self.detached.fetch_update(Relaxed, Relaxed, |detached| {
+            (detached & SLOTS > 0).then(|| detached - 1)
+        }) {
+            self.release(detached >> 32);
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    fn full(&self) -> bool {
+        self.max_calls
+            .is_some_and(|max_calls| self.taken.load(Relaxed) & SLOTS >= max_calls)
+    }
+
+    /// Frees all the slots, when `HalfOpen(_)` state is entered.
+    fn renew(&self) {
+        let renew = |packed: u64| Some(((packed >> 32) + 1) << 32);
+        let _ = self.taken.fetch_update(Relaxed, Relaxed, renew);
+        let _ = self.detached.fetch_update(Relaxed, Relaxed, renew);
+    }
+}
+
+/// A permitted call, that frees the slot it took in `HalfOpen(_)` state, if any, when
+/// dropped, that is once the call completed, panicked or was cancelled.
+#[derive(Debug)]
+pub(crate) struct Permit<'a> {
+    slot: Option<(&'a Probes, u64)>,
+}
+
+impl Permit<'_> {
+    /// Keeps the slot taken, returns its generation to give to
+    /// `Recloser::release_slot(...)` once the call completed.
+    pub(crate) fn into_slot(self) -> Option<u64> {
+        let generation = self.slot.map(|(_, generation)| generation);
+        std::mem::forget(self);
+        generation
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some((probes, generation)) = self.slot {
+            probes.release(generation);
+        }
+    }
+}
+
+/// The failure_rate of a `RingBuffer` along with the number of calls it was
+/// calculated over.
+fn window_stats(rb: &RingBuffer) -> (Option<f32>, usize) {
+    (rb.failure_rate(), rb.calls())
+}
+
+const STATE_SHIFT: u32 = 61;
+const TIME_MASK: u64 = (1 << STATE_SHIFT) - 1;
+
+fn pack(state: State, time: u64) -> u64 {
+    ((state as u64) << STATE_SHIFT) | time.min(TIME_MASK)
+}
+
+fn unpack(packed: u64) -> (State, u64) {
+    let state = match packed >> STATE_SHIFT {
+        0 => State::Closed,
+        1 => State::Open,
+        2 => State::HalfOpen,
+        3 => State::Probe,
+        _ => State::ForcedOpen,
+    };
+    (state, packed & TIME_MASK)
+}
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_nanos().min(TIME_MASK as u128) as u64
+}
+
+/// Escalation settings, see `RecloserBuilder::escalate(...)`.
+#[derive(Debug)]
+struct EscalationPolicy {
+    reopens: usize,
+    hook: Hook<Escalation>,
+    forced_open: bool,
+}
+
+/// A helper struct to build customized `Recloser`.
+#[derive(Debug)]
+pub struct RecloserBuilder<W = DefaultWait, P = AnyError> {
+    wait: W,
+    predicate: P,
+    settings: Settings,
+}
+
+/// Settings of a `RecloserBuilder` that do not depend on its type parameters.
+#[derive(Debug)]
+struct Settings {
+    name: Option<String>,
+    #[cfg(feature = "tracing")]
+    trace_calls: Option<u32>,
+    #[cfg(feature = "tokio-metrics")]
+    task_monitor: Option<tokio_metrics::TaskMonitor>,
+    threshold: f32,
+    closed_len: usize,
+    closed_half_life: Option<f32>,
+    closed_time_window: Option<(usize, Duration)>,
+    failure_count: Option<u32>,
+    minimum_calls: Option<usize>,
+    half_open_len: usize,
+    half_open_min_calls: Option<usize>,
+    half_open_successes: Option<usize>,
+    half_open_max_calls: Option<usize>,
+    fuse_wait: Duration,
+    rate_jump: Option<(f32, Duration)>,
+    anomaly: Option<(Deviation, Duration)>,
+    slo: Option<Slo>,
+    trip_policy: Option<TripPolicy>,
+    categories: Vec<Category>,
+    warn_at: Option<f32>,
+    on_warning: Option<Hook<Warning>>,
+    suspend_probe: Option<u32>,
+    escalation: Option<EscalationPolicy>,
+    escalate_forced_open: bool,
+    flap_latch: Option<(usize, Duration)>,
+    listeners: Listeners,
+    on_call: Option<Hook<CallOutcome>>,
+    history: Option<usize>,
+    rolling_windows: Vec<Duration>,
+    restore: Option<Snapshot>,
+    start_open: Option<Duration>,
+    warm_up: Duration,
+    cooldown: Option<Duration>,
+    idle_reset: Option<Duration>,
+    half_open_ttl: Option<Duration>,
+    half_open_ttl_closes: bool,
+    disabled: bool,
+}
+
+impl RecloserBuilder {
+    fn new() -> Self {
+        RecloserBuilder {
+            wait: DefaultWait::default(),
+            predicate: AnyError,
+            settings: Settings {
+                name: None,
+                #[cfg(feature = "tracing")]
+                trace_calls: None,
+                #[cfg(feature = "tokio-metrics")]
+                task_monitor: None,
+                threshold: 0.5,
+                closed_len: 100,
+                closed_half_life: None,
+                closed_time_window: None,
+                failure_count: None,
+                minimum_calls: None,
+                half_open_len: 10,
+                half_open_min_calls: None,
+                half_open_successes: None,
+                half_open_max_calls: None,
+                fuse_wait: Duration::from_secs(10 * 60),
+                rate_jump: None,
+                anomaly: None,
+                slo: None,
+                trip_policy: None,
+                categories: Vec::new(),
+                warn_at: None,
+                on_warning: None,
+                suspend_probe: None,
+                escalation: None,
+                escalate_forced_open: false,
+                flap_latch: None,
+                listeners: Listeners::default(),
+                on_call: None,
+                history: None,
+                rolling_windows: Vec::new(),
+                restore: None,
+                start_open: None,
+                warm_up: Duration::ZERO,
+                cooldown: None,
+                idle_reset: None,
+                half_open_ttl: None,
+                half_open_ttl_closes: false,
+                disabled: false,
+            },
+        }
+    }
+}
+
+impl<P> RecloserBuilder<DefaultWait, P> {
+    pub fn open_wait(mut self, open_wait: Duration) -> Self {
+        self.wait = DefaultWait(open_wait);
+        self
+    }
+}
+
+impl<W, P> RecloserBuilder<W, P> {
+    /// Names the `Recloser`, for instance to tell breakers apart in `tracing` events.
+    /// It is set by `register_recloser!(...)` to the registered name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.settings.name = Some(name.into());
+        self
+    }
+
+    /// Wraps at most `per_sec` `call(...)`s and `call_with(...)`s per second in a
+    /// `recloser_call` span, with the `breaker_name` and `state` at entry, along with
+    /// the `outcome` and `latency` (in seconds) recorded on completion. Transitions
+    /// are always traced regardless.
+    #[cfg(feature = "tracing")]
+    pub fn trace_calls(mut self, per_sec: u32) -> Self {
+        self.settings.trace_calls = Some(per_sec);
+        self
+    }
+
+    /// Instruments the futures wrapped by an `AsyncRecloser` with `monitor`, so that
+    /// tokio-metrics reports their poll and scheduling times, and the number of them
+    /// in-flight as the difference of its `instrumented_count` and `dropped_count`.
+    #[cfg(feature = "tokio-metrics")]
+    pub fn task_monitor(mut self, monitor: tokio_metrics::TaskMonitor) -> Self {
+        self.settings.task_monitor = Some(monitor);
+        self
+    }
+
+    pub fn error_rate(mut self, threshold: f32) -> Self {
+        self.settings.threshold = threshold;
+        self
+    }
+
+    pub fn closed_len(mut self, closed_len: usize) -> Self {
+        self.settings.closed_len = closed_len.max(1);
+        self
+    }
+
+    /// Weights the failure_rate calculated in `Closed(_)` state by recency: a call
+    /// recorded `half_life` calls ago counts half as much as the latest one. Smooths
+    /// the effect of calls leaving the window, especially with a small `closed_len`.
+    pub fn closed_half_life(mut self, half_life: f32) -> Self {
+        self.settings.closed_half_life = Some(half_life);
+        self
+    }
+
+    /// Calculates the failure_rate in `Closed(_)` state over the calls of the last `buckets`
+    /// time buckets of `width` each, for instance 10 buckets of 1 second, so that old calls
+    /// age out smoothly. `closed_len` is then the minimum number of calls the window must
+    /// hold before the failure_rate is calculated.
+    pub fn closed_time_window(mut self, buckets: usize, width: Duration) -> Self {
+        self.settings.closed_time_window = Some((buckets, width));
+        self
+    }
+
+    /// Also trips in `Closed(_)` state as soon as the window holds at least `count`
+    /// failures regardless of the failure_rate, weighted as by `ErrorPredicate::weight(...)`,
+    /// so that low volume endpoints do not wait for a whole window to react.
+    pub fn failure_count(mut self, count: u32) -> Self {
+        self.settings.failure_count = Some(count);
+        self
+    }
+
+    /// Never trips in `Closed(_)` state until the window holds at least `min_calls`
+    /// calls, the failure_rate is then calculated over the calls recorded so far.
+    /// Defaults to `closed_len`, it is capped to it unless `closed_time_window(...)` is set.
+    pub fn minimum_calls(mut self, min_calls: usize) -> Self {
+        self.settings.minimum_calls = Some(min_calls);
+        self
+    }
+
+    pub fn half_open_len(mut self, half_open_len: usize) -> Self {
+        self.settings.half_open_len = half_open_len.max(1);
+        self
+    }
+
+    /// Decides the transition out of `HalfOpen(_)` state every `min_calls` calls, based
+    /// on the failure_rate of the last `half_open_len` calls at most. Defaults to
+    /// `half_open_len`, it is capped to it.
+    pub fn half_open_min_calls(mut self, min_calls: usize) -> Self {
+        self.settings.half_open_min_calls = Some(min_calls);
+        self
+    }
+
+    /// Transitions from `HalfOpen(_)` to `Closed(_)` state only after `successes`
+    /// consecutive successful calls, regardless of `half_open_len`. The failure_rate of
+    /// the `HalfOpen(_)` window then only decides whether to reopen.
+    pub fn half_open_successes(mut self, successes: usize) -> Self {
+        self.settings.half_open_successes = Some(successes);
+        self
+    }
+
+    /// Permits at most `max_calls` calls at once in `HalfOpen(_)` state and rejects the
+    /// others, so that a recovering service is not flooded with probes. A slot is freed
+    /// once its call completed, panicked or was cancelled.
+    pub fn half_open_max_calls(mut self, max_calls: usize) -> Self {
+        self.settings.half_open_max_calls = Some(max_calls);
+        self
+    }
+
+    /// Transitions back to `Open(_)` state when `HalfOpen(_)` state lasted longer than
+    /// `ttl` without deciding, so that low traffic services do not get stuck in it.
+    /// Such transitions are caused by `TransitionCause::Expired`.
+    pub fn half_open_ttl(mut self, ttl: Duration) -> Self {
+        self.settings.half_open_ttl = Some(ttl);
+        self
+    }
+
+    /// When `half_open_ttl(...)` elapsed, transitions to `Closed(_)` state instead of
+    /// `Open(_)` state.
+    pub fn half_open_ttl_closes(mut self) -> Self {
+        self.settings.half_open_ttl_closes = true;
+        self
+    }
+
+    /// Time spent in `Open(_)` state after a critical failure, see `ErrorPredicate::is_fuse(...)`.
+    /// Defaults to 10 minutes.
+    pub fn fuse_wait(mut self, fuse_wait: Duration) -> Self {
+        self.settings.fuse_wait = fuse_wait;
+        self
+    }
+
+    /// Also trips in `Closed(_)` state when the failure_rate increases by at least
+    /// `delta` within `within`, even though `error_rate` has not been reached yet.
+    pub fn error_rate_jump(mut self, delta: f32, within: Duration) -> Self {
+        self.settings.rate_jump = Some((delta, within));
+        self
+    }
+
+    /// Replaces the fixed `error_rate` by a dynamic one, learned from the failure_rates
+    /// observed in `Closed(_)` state over roughly `horizon`. Trips when the failure_rate
+    /// deviates from this baseline by at least `z_score` standard deviations.
+    /// The fixed `error_rate` is still used until enough failure_rates have been observed.
+    pub fn error_rate_anomaly(mut self, z_score: f32, horizon: Duration) -> Self {
+        self.settings.anomaly = Some((Deviation::ZScore(z_score), horizon));
+        self
+    }
+
+    /// Replaces the fixed `error_rate` by one relative to the baseline failure_rate
+    /// observed in `Closed(_)` state over roughly `horizon`. Trips when the failure_rate
+    /// reaches `ratio` times this baseline, for instance `5.0` for dependencies failing
+    /// steadily at a low rate. The fixed `error_rate` is still used until enough
+    /// failure_rates have been observed.
+    pub fn error_rate_baseline(mut self, ratio: f32, horizon: Duration) -> Self {
+        self.settings.anomaly = Some((Deviation::Ratio(ratio), horizon));
+        self
+    }
+
+    /// Also trips in `Closed(_)` state when the error budget of the `slo` is burning
+    /// too fast.
+    pub fn slo(mut self, slo: Slo) -> Self {
+        self.settings.slo = Some(slo);
+        self
+    }
+
+    /// Trips in `Closed(_)` state when the `policy` fires after a failed call, instead of
+    /// when the failure_rate reaches `error_rate`. Such trips are caused by
+    /// `TransitionCause::Policy`, with the name of the clause that fired.
+    pub fn trip_policy(mut self, policy: TripPolicy) -> Self {
+        self.settings.trip_policy = Some(policy);
+        self
+    }
+
+    /// Also trips in `Closed(_)` state when the failure_rate of the errors in `category`,
+    /// as returned by `ErrorPredicate::category(...)`, reaches `threshold` over the last
+    /// `len` calls. Can be called several times, for instance to trip at 10% of timeouts
+    /// but at 50% of 5xx responses. Such trips are caused by `TransitionCause::Category`.
+    pub fn category(mut self, category: &'static str, len: usize, threshold: f32) -> Self {
+        self.settings
+            .categories
+            .push(Category::new(category, len, threshold));
+        self
+    }
+
+    /// Emits a `Warning` when the failure_rate in `Closed(_)` state reaches `fraction`
+    /// of the threshold. A new one will only be emitted once the failure_rate went
+    /// clearly below this level again.
+    pub fn warn_at(mut self, fraction: f32) -> Self {
+        self.settings.warn_at = Some(fraction);
+        self
+    }
+
+    /// Registers a callback invoked on each `Warning`, defaults `warn_at` to `0.8`.
+    pub fn on_warning<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Warning) + Send + Sync + 'static,
+    {
+        self.settings.on_warning = Some(Hook::new(f));
+        self
+    }
+
+    /// Invokes `f` with the outcome of every call, including rejected ones, for instance
+    /// to emit per call metrics. It is invoked on the calling thread so it should not block.
+    pub fn on_call<F>(mut self, f: F) -> Self
+    where
+        F: Fn(CallOutcome) + Send + Sync + 'static,
+    {
+        self.settings.on_call = Some(Hook::new(move |outcome: &CallOutcome| f(*outcome)));
+        self
+    }
+
+    /// Registers a `TransitionListener`, for instance to emit metrics on each
+    /// transition. Several listeners can be registered, they are notified in order.
+    pub fn on_transition<L: TransitionListener + 'static>(mut self, listener: L) -> Self {
+        self.settings.listeners.push(listener);
+        self
+    }
+
+    /// Keeps the last `len` transitions, along with the failure_rate that led to them,
+    /// so that they can be inspected with `Recloser::history()`.
+    pub fn history(mut self, len: usize) -> Self {
+        self.settings.history = Some(len);
+        self
+    }
+
+    /// Also counts the calls performed over a rolling `window`, can be called several
+    /// times to monitor trends over different horizons with `Recloser::rolling_stats()`.
+    pub fn rolling_window(mut self, window: Duration) -> Self {
+        self.settings.rolling_windows.push(window);
+        self
+    }
+
+    /// When the first call after `Open(_)` state happens more than `factor` times
+    /// `open_wait` later than expected (host suspended, VM paused, etc.), only allows
+    /// a single probe call before transitioning to `HalfOpen(_)` state.
+    /// If this call fails, transitions back to `Open(_)` state instead.
+    pub fn suspend_probe(mut self, factor: u32) -> Self {
+        self.settings.suspend_probe = Some(factor);
+        self
+    }
+
+    /// Invokes `f` once the `Recloser` transitioned `reopens` consecutive times from
+    /// `HalfOpen(_)` back to `Open(_)` state, that is without being `Closed(_)` in between.
+    pub fn escalate<F>(mut self, reopens: usize, f: F) -> Self
+    where
+        F: Fn(&Escalation) + Send + Sync + 'static,
+    {
+        self.settings.escalation = Some(EscalationPolicy {
+            reopens,
+            hook: Hook::new(f),
+            forced_open: false,
+        });
+        self
+    }
+
+    /// When escalating, transitions to `ForcedOpen` state instead of `Open(_)` state.
+    /// All calls are then rejected until `Recloser::force_close()` is called.
+    pub fn escalate_forced_open(mut self) -> Self {
+        self.settings.escalate_forced_open = true;
+        self
+    }
+
+    /// Latches `ForcedOpen` state once the `Recloser` transitioned `flaps` times from
+    /// `HalfOpen(_)` back to `Open(_)` state within `within`, as persistent flapping
+    /// usually needs human attention. All calls are then rejected until
+    /// `Recloser::force_close()` or `Recloser::reset()` is called, the transition is
+    /// caused by `TransitionCause::Flapping`.
+    pub fn flap_latch(mut self, flaps: usize, within: Duration) -> Self {
+        self.settings.flap_latch = Some((flaps, within));
+        self
+    }
+
+    /// Warm starts the `Recloser` from a `Snapshot` taken by a previous process, so that
+    /// it survives restarts rather than hammering a broken dependency once `Closed(_)`.
+    /// Its state, its `retry_after` and its `flaps` are restored, the time elapsed since
+    /// it was taken being deducted from `retry_after`: once it elapsed, the `Recloser`
+    /// starts in `HalfOpen(_)` state. Takes precedence over `start_open(...)`.
+    pub fn restore(mut self, snapshot: Snapshot) -> Self {
+        self.settings.restore = Some(snapshot);
+        self
+    }
+
+    /// Starts the `Recloser` in `Open(_)` state for `wait`, for instance during a cold
+    /// start when the dependency is known to be down. It then transitions to `HalfOpen(_)`
+    /// state as usual. Ignored when starting `disabled()`.
+    pub fn start_open(mut self, wait: Duration) -> Self {
+        self.settings.start_open = Some(wait);
+        self
+    }
+
+    /// Clears the `Closed(_)` window when no call was recorded for longer than `idle`, so
+    /// that stale failures do not combine with fresh ones on low traffic services.
+    pub fn idle_reset(mut self, idle: Duration) -> Self {
+        self.settings.idle_reset = Some(idle);
+        self
+    }
+
+    /// Records failures as usual during `grace` once built, but without tripping, so that
+    /// connection pools and DNS caches can warm up without opening spuriously.
+    pub fn warm_up(mut self, grace: Duration) -> Self {
+        self.settings.warm_up = grace;
+        self
+    }
+
+    /// Stays at least `cooldown` in `Closed(_)` and `HalfOpen(_)` states before the
+    /// recorded calls can transition out of them, including once built, so that bursty
+    /// traffic does not make it flap. Manual transitions, fuses and timeouts such as
+    /// `half_open_ttl(...)` are not held back.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.settings.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Starts the `Recloser` disabled, see `Recloser::set_disabled(...)`.
+    pub fn disabled(mut self) -> Self {
+        self.settings.disabled = true;
+        self
+    }
+
+    /// Replaces the `DefaultWait` strategy, that always waits for `open_wait`.
+    pub fn wait_strategy<V: WaitStrategy>(self, wait: V) -> RecloserBuilder<V, P> {
+        RecloserBuilder {
+            wait,
+            predicate: self.predicate,
+            settings: self.settings,
+        }
+    }
+
+    /// Replaces the `AnyError` predicate used by `Recloser::call(...)` and
+    /// `AsyncRecloser::call(...)`, `call_with(...)` still overrides it.
+    pub fn default_predicate<Q>(self, predicate: Q) -> RecloserBuilder<W, Q> {
+        RecloserBuilder {
+            wait: self.wait,
+            predicate,
+            settings: self.settings,
+        }
+    }
+
+    pub fn build(self) -> Recloser<W, P> {
+        let settings = self.settings;
+        let restored = settings.restore.map(|snapshot| match snapshot.state {
+            CircuitState::Closed => (State::Closed, 0),
+            CircuitState::Open => {
+                let elapsed = snapshot.taken_at.elapsed().unwrap_or_default();
+                match snapshot
+                    .retry_after
+                    .unwrap_or_default()
+                    .saturating_sub(elapsed)
+                {
+                    Duration::ZERO => (State::HalfOpen, 0),
+                    wait => (State::Open, nanos(wait)),
+                }
+            }
+            CircuitState::HalfOpen => (State::HalfOpen, 0),
+            CircuitState::ForcedOpen => (State::ForcedOpen, 0),
+        });
+        let opened = settings.start_open.map(|wait| (State::Open, nanos(wait)));
+        let state = match restored.or(opened) {
+            Some((state, time)) if !settings.disabled => pack(state, time),
+            _ => pack(State::Closed, 0),
+        };
+        let transitions: [[AtomicU64; 4]; 4] = Default::default();
+        if let Some(snapshot) = settings.restore {
+            transitions[CircuitState::Closed as usize][CircuitState::Open as usize]
+                .store(snapshot.flaps, Relaxed);
+        }
+        Recloser {
+            name: settings.name,
+            #[cfg(feature = "tracing")]
+            trace_calls: settings.trace_calls.map(CallSampler::new),
+            #[cfg(feature = "tokio-metrics")]
+            task_monitor: settings.task_monitor,
+            threshold: AtomicU32::new(settings.threshold.to_bits()),
+            wait: self.wait,
+            predicate: self.predicate,
+            rate_jump: settings
+                .rate_jump
+                .map(|(delta, within)| RateJump::new(delta, within)),
+            anomaly: settings
+                .anomaly
+                .map(|(deviation, horizon)| Anomaly::new(deviation, horizon)),
+            slo: settings.slo,
+            slow_calls: settings
+                .trip_policy
+                .as_ref()
+                .filter(|policy| policy.timed())
+                .map(|_| SlowCalls::new(settings.closed_len)),
+            trip_policy: settings.trip_policy,
+            consecutive_failures: AtomicU32::new(0),
+            categories: settings.categories,
+            warner: match (settings.warn_at, settings.on_warning) {
+                (None, None) => None,
+                (warn_at, hook) => Some(Warner::new(warn_at.unwrap_or(0.8), hook)),
+            },
+            suspend_probe: settings.suspend_probe,
+            escalation: settings.escalation.map(|escalation| EscalationPolicy {
+                forced_open: settings.escalate_forced_open,
+                ..escalation
+            }),
+            flap_latch: settings
+                .flap_latch
+                .map(|(flaps, within)| (flaps.max(1) as u64, RollingCounter::new(within))),
+            listeners: settings.listeners,
+            on_call: settings.on_call,
+            subscribers: Subscribers::default(),
+            history: settings.history.map(History::new),
+            counters: Counters::new(settings.rolling_windows),
+            uptime: Uptime::default(),
+            trip_error: std::sync::Mutex::new(None),
+            open_category: std::sync::Mutex::new(None),
+            half_open_min_calls: settings.half_open_min_calls,
+            half_open_successes: settings.half_open_successes,
+            successes: AtomicUsize::new(0),
+            probes: Probes::new(settings.half_open_max_calls),
+            fuse_wait: settings.fuse_wait,
+            #[cfg(any(feature = "trace", feature = "proptest"))]
+            skew: AtomicU64::new(0),
+            #[cfg(feature = "tokio")]
+            cancel: std::sync::Mutex::new(None),
+            #[cfg(feature = "tokio")]
+            watch: tokio::sync::watch::Sender::new(unpack(state).0.public()),
+            reopens: AtomicUsize::new(0),
+            disabled: AtomicBool::new(settings.disabled),
+            warm_up: nanos(settings.warm_up),
+            cooldown: settings.cooldown.map(nanos),
+            idle_reset: settings.idle_reset.map(nanos),
+            half_open_ttl: settings
+                .half_open_ttl
+                .map(|ttl| match settings.half_open_ttl_closes {
+                    true => (nanos(ttl), State::Closed),
+                    false => (nanos(ttl), State::Open),
+                }),
+            last_recorded: AtomicU64::new(0),
+            closed_len: AtomicUsize::new(settings.closed_len),
+            half_open_len: AtomicUsize::new(settings.half_open_len),
+            transitions,
+            origin: Instant::now(),
+            state: AtomicU64::new(state),
+            closed_rb: Atomic::new(
+                match settings.closed_half_life {
+                    Some(half_life) => RingBuffer::new(settings.closed_len).decayed(half_life),
+                    None => RingBuffer::new(settings.closed_len),
+                }
+                .with_generation(state),
+            ),
+            closed_time_window: settings
+                .closed_time_window
+                .map(|(buckets, width)| TimeWindow::new(buckets, width)),
+            failure_count: settings.failure_count,
+            minimum_calls: settings.minimum_calls,
+            half_open_rb: Atomic::new(
+                RingBuffer::new(settings.half_open_len).with_generation(state),
+            ),
+        }
+    }
+}
+
+impl Default for Recloser {
+    fn default() -> Self {
+        Recloser::custom().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use fake_clock::FakeClock;
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::error::SlowerThan;
+    use crate::wait::BoxedWait;
+
+    fn sleep(time: u64) {
+        FakeClock::advance_time(time);
+    }
+
+    #[test]
+    fn packed_state() {
+        for state in [
+            State::Closed,
+            State::Open,
+            State::HalfOpen,
+            State::Probe,
+            State::ForcedOpen,
+        ] {
+            assert_eq!((state, 0), unpack(pack(state, 0)));
+            assert_eq!((state, TIME_MASK), unpack(pack(state, TIME_MASK)));
+        }
+    }
+
+    #[test]
+    fn multi_errors() {
+        let recl = Recloser::custom().closed_len(1).build();
+        let guard = &epoch::pin();
+
+        let f = || Err::<(), ()>(());
+        assert!(matches!(recl.call(f), Err(Error::Inner(()))));
+        assert!(recl.call_permitted(guard));
+
+        let f = || Err::<(), usize>(12);
+        assert!(matches!(recl.call(f), Err(Error::Inner(12))));
+        assert!(!recl.call_permitted(guard));
+    }
+
+    #[test]
+    fn error_predicate() {
+        let recl = Recloser::custom().closed_len(1).build();
+        let guard = &epoch::pin();
+
+        let f = || Err::<(), ()>(());
+        let p = |_: &()| false;
+
+        assert!(matches!(recl.call_with(p, f), Err(Error::Inner(()))));
+        assert!(recl.call_permitted(guard));
+
+        assert!(matches!(recl.call_with(p, f), Err(Error::Inner(()))));
+        assert!(recl.call_permitted(guard));
+    }
+
+    #[test]
+    fn default_predicate() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .default_predicate(|err: &usize| *err > 10)
+            .build();
+        let guard = &epoch::pin();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                recl.call(|| Err::<(), _>(1)),
+                Err(Error::Inner(1))
+            ));
+        }
+        assert!(recl.call_permitted(guard));
+
+        // Still overridden by call_with
+        for _ in 0..2 {
+            let _ = recl.call_with(|_: &usize| false, || Err::<(), _>(12));
+        }
+        assert!(recl.call_permitted(guard));
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), _>(12));
+        }
+        assert!(!recl.call_permitted(guard));
+    }
+
+    #[test]
+    fn recloser_correctness() {
+        let recl = Recloser::custom()
+            .error_rate(0.5)
+            .closed_len(2)
+            .half_open_len(2)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        // Fill the State::Closed ring buffer
+        for _ in 0..2 {
+            assert!(matches!(
+                recl.call(|| Err::<(), ()>(())),
+                Err(Error::Inner(()))
+            ));
+            assert_eq!(State::Closed, recl.current_state());
+        }
+
+        // Transition to State::Open on next call
+        assert!(matches!(
+            recl.call(|| Err::<(), ()>(())),
+            Err(Error::Inner(()))
+        ));
+        assert_eq!(State::Open, recl.current_state());
+        assert!(matches!(
+            recl.call(|| Err::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+
+        // Transition to State::HalfOpen on first call after 1 sec
+        sleep(1500);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        // Fill the State::HalfOpen ring buffer
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        // Transition to State::Closed when failure rate below threshold
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(State::Closed, recl.current_state());
+    }
+
+    #[test]
+    fn half_open_batched() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(4)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        // Failures only decide once the window is full
+        sleep(1500);
+        for _ in 0..4 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+            assert_eq!(State::HalfOpen, recl.current_state());
+        }
+
+        // A success above the threshold does not decide
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        // A failure evaluated on the next window reopens
+        for _ in 0..4 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn half_open_min_calls() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(10)
+            .half_open_min_calls(2)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        // Decides after a few probes, without waiting for the whole window
+        sleep(1500);
+        for _ in 0..2 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+            assert_eq!(State::HalfOpen, recl.current_state());
+        }
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(State::Closed, recl.current_state());
+    }
+
+    #[test]
+    fn half_open_successes() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(10)
+            .half_open_successes(3)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        // A failed probe breaks the streak of successes
+        sleep(1500);
+        for _ in 0..2 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        let _ = recl.call(|| Err::<(), ()>(()));
+        for _ in 0..2 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+            assert_eq!(State::HalfOpen, recl.current_state());
+        }
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(State::Closed, recl.current_state());
+    }
+
+    #[test]
+    fn half_open_max_calls() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(10)
+            .half_open_max_calls(2)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        let guard = &epoch::pin();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        sleep(1500);
+        let permit = recl.permit(guard);
+        assert!(permit.is_some());
+        assert!(recl.call_permitted(guard));
+        assert!(recl.permit(guard).is_none());
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        // A slot is freed once a call completed, or its permit dropped
+        recl.on_success(guard);
+        recl.release_detached();
+        assert!(recl.call_permitted(guard));
+        assert!(!recl.call_permitted(guard));
+        drop(permit);
+
+        // And when its call panicked
+        let probe = || recl.call(|| -> Result<(), ()> { panic!("probe") });
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(probe)).is_err());
+        assert!(recl.call_permitted(guard));
+    }
+
+    #[test]
+    fn closed_half_life() {
+        let recl = Recloser::custom()
+            .error_rate(0.5)
+            .closed_len(4)
+            .closed_half_life(1.0)
+            .build();
+
+        // The window has a single failure, the oldest call, that barely counts
+        for failed in [true, true, false, false, false] {
+            let _ = recl.call(|| if failed { Err(()) } else { Ok(()) });
+        }
+        assert_eq!(State::Closed, recl.current_state());
+
+        // The window still has a single failure, but it is the latest call
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn fuse() {
+        let recl = Recloser::custom()
+            .closed_len(10)
+            .open_wait(Duration::from_secs(1))
+            .fuse_wait(Duration::from_secs(60))
+            .default_predicate(AnyError.fuse_on(|err: &usize| *err == 403))
+            .build();
+
+        assert!(matches!(
+            recl.call(|| Err::<(), _>(500)),
+            Err(Error::Inner(500))
+        ));
+        assert_eq!(State::Closed, recl.current_state());
+
+        // Opens regardless of the failure_rate, for fuse_wait
+        assert!(matches!(
+            recl.call(|| Err::<(), _>(403)),
+            Err(Error::Inner(403))
+        ));
+        assert_eq!(State::Open, recl.current_state());
+        sleep(30_000);
+        assert!(matches!(
+            recl.call(|| Ok::<(), usize>(())),
+            Err(Error::Rejected)
+        ));
+        sleep(31_000);
+        assert!(matches!(recl.call(|| Ok::<(), usize>(())), Ok(())));
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        recl.force_open();
+        recl.on_err(&recl.predicate, &403, &epoch::pin());
+        assert_eq!(State::ForcedOpen, recl.current_state());
+    }
+
+    #[test]
+    fn error_rate_jump() {
+        let recl = Recloser::custom()
+            .error_rate(0.9)
+            .closed_len(10)
+            .error_rate_jump(0.3, Duration::from_secs(10))
+            .build();
+
+        // Fill the State::Closed ring buffer with successes
+        for _ in 0..11 {
+            assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        }
+
+        // Failure rate jumps from 0.0 to 0.3
+        for _ in 0..3 {
+            assert_eq!(State::Closed, recl.current_state());
+            assert!(matches!(
+                recl.call(|| Err::<(), ()>(())),
+                Err(Error::Inner(()))
+            ));
+        }
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn error_rate_anomaly() {
+        let recl = Recloser::custom()
+            .error_rate(0.9)
+            .closed_len(10)
+            .error_rate_anomaly(3.0, Duration::from_secs(3600))
+            .build();
+
+        // Learn a steady 20% failure rate baseline
+        for i in 0..100 {
+            let _ = recl.call(|| if i % 5 == 0 { Err(()) } else { Ok(()) });
+        }
+        assert_eq!(State::Closed, recl.current_state());
+
+        // Trips way before reaching the fixed error rate
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn error_rate_baseline() {
+        let recl = Recloser::custom()
+            .error_rate(0.9)
+            .closed_len(10)
+            .error_rate_baseline(1.5, Duration::from_secs(3600))
+            .build();
+
+        // Learn a steady 20% failure rate baseline
+        for i in 0..100 {
+            let _ = recl.call(|| if i % 5 == 0 { Err(()) } else { Ok(()) });
+        }
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(State::Closed, recl.current_state());
+
+        // Trips at 30%, that is 1.5 times the baseline
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn open_wait_by_category() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .wait_strategy(|ctx: &WaitContext| match ctx.category {
+                Some("throttled") => Duration::from_secs(60),
+                _ => Duration::from_secs(5),
+            })
+            .default_predicate(
+                AnyError
+                    .categorize(|err: &&str| err.starts_with("throttled").then_some("throttled")),
+            )
+            .build();
+
+        let _ = recl.call(|| Err::<(), _>("refused"));
+        let _ = recl.call(|| Err::<(), _>("throttled"));
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            recl.rejection().and_then(|r| r.retry_after)
+        );
+
+        recl.reset();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), _>("refused"));
+        }
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            recl.rejection().and_then(|r| r.retry_after)
+        );
+    }
+
+    #[test]
+    fn rejection_info() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .escalate(1, |_| ())
+            .escalate_forced_open()
+            .build();
+        assert_eq!(None, recl.rejection());
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        sleep(400);
+        assert_eq!(
+            Some(Rejection {
+                state: CircuitState::Open,
+                retry_after: Some(Duration::from_millis(600)),
+            }),
+            recl.rejection()
+        );
+
+        sleep(1000);
+        assert_eq!(None, recl.rejection());
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(
+            Some(Rejection {
+                state: CircuitState::ForcedOpen,
+                retry_after: None,
+            }),
+            recl.rejection()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_snapshot() {
+        let recl = Recloser::custom().closed_len(1).build();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        recl.open_for(Duration::from_secs(2));
+
+        let snapshot = recl.snapshot();
+        assert_eq!(1, snapshot.flaps);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let mut value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert!(value["taken_at"]["secs_since_epoch"].as_u64().unwrap() > 0);
+        value.as_object_mut().unwrap().remove("taken_at");
+        let expected = r#"{"state":"open","failure_rate":null,"retry_after":2.0,"threshold":0.5,"metrics":{"calls":3,"successes":0,"failures":2,"rejected":1,"in_flight":0,"failure_rate":null},"flaps":1}"#;
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(expected).unwrap(),
+            value
+        );
+        assert_eq!(snapshot, serde_json::from_str(&json).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_rejection() {
+        let rejection = Rejection {
+            state: CircuitState::Open,
+            retry_after: Some(Duration::from_millis(1500)),
+        };
+        assert_eq!(
+            r#"{"state":"open","retry_after":1.5}"#,
+            serde_json::to_string(&rejection).unwrap()
+        );
+
+        let err = Error::Inner(12);
+        assert_eq!(
+            r#"{"kind":"inner","error":12}"#,
+            serde_json::to_string(&err).unwrap()
+        );
+        let err = Error::<usize>::Rejected;
+        assert_eq!(
+            r#"{"kind":"rejected"}"#,
+            serde_json::to_string(&err).unwrap()
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn cancellation_token() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+
+        let token = recl.cancellation_token();
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert!(!token.is_cancelled());
+
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert!(token.is_cancelled());
+
+        // A new token is handed out afterward
+        let token = recl.cancellation_token();
+        assert!(!token.is_cancelled());
+        sleep(1500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn suspend_probe() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .suspend_probe(10)
+            .build();
+
+        let guard = &epoch::pin();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        // A regular delay goes to State::HalfOpen
+        sleep(1500);
+        assert!(recl.call_permitted(guard));
+        assert_eq!(State::HalfOpen, recl.current_state());
+        recl.on_error(guard);
+        recl.on_error(guard);
+        assert_eq!(State::Open, recl.current_state());
+
+        // A huge delay only allows a single probe
+        sleep(60_000);
+        assert!(recl.call_permitted(guard));
+        assert!(!recl.call_permitted(guard));
+        recl.on_error(guard);
+        assert_eq!(State::Open, recl.current_state());
+
+        sleep(60_000);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(State::HalfOpen, recl.current_state());
+    }
+
+    #[test]
+    fn suspend_probe_overflow() {
+        let huge = Arc::new(AtomicBool::new(false));
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .wait_strategy({
+                let huge = huge.clone();
+                move |_: &WaitContext| match huge.load(Relaxed) {
+                    true => Duration::MAX,
+                    false => Duration::from_secs(1),
+                }
+            })
+            .suspend_probe(u32::MAX)
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(State::Open, recl.current_state());
+
+        // The overflowing delay never suspends probing
+        huge.store(true, Relaxed);
+        sleep(1500);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(State::HalfOpen, recl.current_state());
+    }
+
+    #[test]
+    fn flap_latch() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .flap_latch(2, Duration::from_secs(60))
+            .build();
+        let reopen = || {
+            sleep(1500);
+            for _ in 0..2 {
+                let _ = recl.call(|| Err::<(), ()>(()));
+            }
+        };
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        reopen();
+        assert_eq!(State::Open, recl.current_state());
+
+        // The first reopen left the window
+        sleep(60_000);
+        reopen();
+        assert_eq!(State::Open, recl.current_state());
+        reopen();
+        assert_eq!(State::ForcedOpen, recl.current_state());
+
+        sleep(60_000);
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+        assert!(recl.force_close());
+        reopen();
+        assert_eq!(State::Open, recl.current_state());
+    }
+
+    #[test]
+    fn escalation() {
+        let escalations = Arc::new(AtomicUsize::new(0));
+        let counter = escalations.clone();
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .escalate_forced_open()
+            .escalate(2, move |e| {
+                assert_eq!(2, e.reopens);
+                counter.fetch_add(1, Relaxed);
+            })
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        for _ in 0..2 {
+            sleep(1500);
+            for _ in 0..2 {
+                let _ = recl.call(|| Err::<(), ()>(()));
+            }
+        }
+        assert_eq!(1, escalations.load(Relaxed));
+        assert_eq!(State::ForcedOpen, recl.current_state());
+
+        sleep(1500);
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+
+        assert!(recl.force_close());
+        assert!(!recl.force_close());
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+    }
+
+    #[test]
+    fn public_state() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(2)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, recl.state());
+
+        sleep(1500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::HalfOpen, recl.state());
+    }
+
+    #[test]
+    fn time_until_half_open() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        assert_eq!(None, recl.time_until_half_open());
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(Some(Duration::from_secs(1)), recl.time_until_half_open());
+        sleep(400);
+        assert_eq!(
+            Some(Duration::from_millis(600)),
+            recl.time_until_half_open()
+        );
+        sleep(1000);
+        assert_eq!(Some(Duration::ZERO), recl.time_until_half_open());
+
+        recl.force_open();
+        assert_eq!(None, recl.time_until_half_open());
+    }
+
+    #[test]
+    fn transition_listener() {
+        let transitions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = transitions.clone();
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .on_transition(move |from, to| recorded.lock().unwrap().push((from, to)))
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        sleep(1500);
+        for _ in 0..2 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        recl.force_open();
+        recl.force_open();
+
+        assert_eq!(
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+                (CircuitState::Closed, CircuitState::ForcedOpen),
+            ],
+            *transitions.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn call_result_with() {
+        let recl = Recloser::custom().closed_len(1).build();
+        let grpc_status = |res: &Result<u8, ()>| !matches!(res, Ok(0));
+
+        assert!(matches!(
+            recl.call_result_with(grpc_status, || Ok(0)),
+            Ok(0)
+        ));
+        assert!(matches!(
+            recl.call_result_with(grpc_status, || Ok(14)),
+            Ok(14)
+        ));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert!(matches!(
+            recl.call_result_with(grpc_status, || Ok(0)),
+            Err(Error::Rejected)
+        ));
+        assert_eq!(1, recl.metrics().failures);
+    }
+
+    #[test]
+    fn call_classified() {
+        let recl = Recloser::custom()
+            .closed_len(2)
+            .fuse_wait(Duration::from_secs(60))
+            .build();
+        let classifier = |res: &Result<u16, u16>| match res {
+            Ok(200) => Outcome::Success,
+            Ok(_) | Err(500) => Outcome::Failure,
+            Err(404) => Outcome::Ignore,
+            Err(_) => Outcome::Fatal,
+        };
+
+        let _ = recl.call_classified(classifier, || Ok(200));
+        let _ = recl.call_classified(classifier, || Err(404));
+        let _ = recl.call_classified(classifier, || Err(404));
+        let _ = recl.call_classified(classifier, || Ok(200));
+        assert_eq!(CircuitState::Closed, recl.state());
+        assert_eq!(2, recl.metrics().successes);
+
+        let _ = recl.call_classified(classifier, || Err(403));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(Some(Duration::from_secs(30)), recl.time_until_half_open());
+        assert_eq!(1, recl.metrics().failures);
+    }
+
+    #[test]
+    fn half_open_last_call() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(4)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        recl.trip();
+        sleep(1500);
+
+        // The failure_rate is below the threshold, but the last call failed
+        for _ in 0..3 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(State::HalfOpen, recl.current_state());
+
+        for _ in 0..4 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        assert_eq!(State::Closed, recl.current_state());
+    }
+
+    #[test]
+    fn fatal() {
+        let recl = Recloser::custom()
+            .open_wait(Duration::from_secs(5))
+            .fuse_wait(Duration::from_secs(60))
+            .history(1)
+            .build();
+
+        // Opens on an empty window, for the open wait
+        let _ = recl.call_classified(|_: &Result<(), ()>| Outcome::Fatal, || Err(()));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(Some(Duration::from_secs(5)), recl.time_until_half_open());
+        assert_eq!(TransitionCause::Fatal, recl.history()[0].cause);
+        assert_eq!(0, recl.closed_rb(&epoch::pin()).calls());
+    }
+
+    #[test]
+    fn ignore_on() {
+        let recl = Recloser::custom()
+            .closed_len(2)
+            .default_predicate(AnyError.ignore_on(|err: &&str| *err == "not found"))
+            .build();
+
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), _>("not found"));
+        }
+        let _ = recl.call(|| Ok::<_, &str>(()));
+        assert_eq!(0, recl.metrics().failures);
+        assert_eq!(1, recl.metrics().calls);
+
+        let _ = recl.call(|| Err::<(), _>("refused"));
+        let _ = recl.call(|| Err::<(), _>("refused"));
+        assert_eq!(CircuitState::Open, recl.state());
+    }
+
+    #[test]
+    fn weighted() {
+        let recl = Recloser::custom()
+            .closed_len(4)
+            .default_predicate(AnyError.weighted(|err: &&str| match *err {
+                "timeout" => 3.0,
+                _ => 0.5,
+            }))
+            .build();
+
+        for _ in 0..4 {
+            let _ = recl.call(|| Ok::<_, &str>(()));
+        }
+        let _ = recl.call(|| Err::<(), _>("throttled"));
+        assert_eq!(Some(0.125), recl.snapshot().failure_rate);
+        let _ = recl.call(|| Err::<(), _>("timeout"));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(2, recl.metrics().failures);
+    }
+
+    #[test]
+    fn category() {
+        let recl = Recloser::custom()
+            .closed_len(100)
+            .category("timeout", 4, 0.5)
+            .history(1)
+            .default_predicate(
+                AnyError.categorize(|err: &&str| err.starts_with("timeout").then_some("timeout")),
+            )
+            .build();
+
+        for err in ["refused", "refused", "refused", "timeout", "refused"] {
+            let _ = recl.call(|| Err::<(), _>(err));
+            assert_eq!(CircuitState::Closed, recl.state());
+        }
+        let _ = recl.call(|| Err::<(), _>("timeout"));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(
+            TransitionCause::Category("timeout"),
+            recl.history()[0].cause
+        );
+    }
+
+    #[test]
+    fn trip_policy() {
+        let recl = Recloser::custom()
+            .closed_len(100)
+            .trip_policy(TripPolicy::failure_rate(0.5).or(TripPolicy::consecutive_failures(3)))
+            .history(1)
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(
+            TransitionCause::Policy("consecutive_failures"),
+            recl.history()[0].cause
+        );
+    }
+
+    #[test]
+    fn slow_call_rate() {
+        let recl = Recloser::custom()
+            .closed_len(4)
+            .trip_policy(TripPolicy::slow_call_rate(0.5, Duration::from_secs(1)))
+            .history(1)
+            .build();
+        let classifier = SlowerThan::new(
+            |_: &Result<(), ()>| Outcome::Success,
+            Duration::from_secs(1),
+        );
+        let call = |ms| {
+            let _ = recl.call_classified(classifier, || {
+                sleep(ms);
+                Ok(())
+            });
+        };
+
+        for ms in [100, 1500, 100] {
+            call(ms);
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
+        call(2000);
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(
+            TransitionCause::Policy("slow_call_rate"),
+            recl.history()[0].cause
+        );
+    }
+
+    #[test]
+    fn slower_than() {
+        let recl = Recloser::default();
+        let classifier = SlowerThan::new(
+            |_: &Result<(), ()>| Outcome::Success,
+            Duration::from_secs(1),
+        );
+
+        let _ = recl.call_classified(classifier, || {
+            sleep(500);
+            Ok(())
+        });
+        let _ = recl.call_classified(classifier, || {
+            sleep(1500);
+            Ok(())
+        });
+        assert_eq!(1, recl.metrics().successes);
+        assert_eq!(1, recl.metrics().failures);
+    }
+
+    #[test]
+    fn trip_error() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .default_predicate(AnyError.describe_with(|err: &String| err.clone()))
+            .build();
+
+        let _ = recl.call(|| Err::<(), _>("early".to_string()));
+        assert_eq!(None, recl.trip_error());
+        let _ = recl.call(|| Err::<(), _>("timeout".to_string()));
+        assert_eq!(Some("timeout".to_string()), recl.trip_error());
+
+        sleep(1500);
+        let _ = recl.call(|| Err::<(), _>("probe".to_string()));
+        assert_eq!(Some("timeout".to_string()), recl.trip_error());
+        let _ = recl.call(|| Err::<(), _>("refused".to_string()));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(Some("refused".to_string()), recl.trip_error());
+
+        // Plain predicates describe nothing
+        recl.reset();
+        let _ = recl.call_with(|_: &String| true, || Err::<(), _>("ignored".to_string()));
+        let _ = recl.call_with(|_: &String| true, || Err::<(), _>("ignored".to_string()));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert_eq!(Some("refused".to_string()), recl.trip_error());
+    }
+
+    #[test]
+    fn on_call() {
+        let outcomes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = outcomes.clone();
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .default_predicate(|err: &bool| *err)
+            .on_call(move |outcome| recorded.lock().unwrap().push(outcome))
+            .build();
+
+        let _ = recl.call(|| Err::<(), bool>(false));
+        let _ = recl.call(|| Err::<(), bool>(true));
+        let _ = recl.call(|| Err::<(), bool>(true));
+        let _ = recl.call(|| Ok::<(), bool>(()));
+
+        assert_eq!(
+            vec![
+                CallOutcome::Success,
+                CallOutcome::Failure,
+                CallOutcome::Rejected,
+                CallOutcome::Rejected,
+            ],
+            *outcomes.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn force_open_and_reset() {
+        let recl = Recloser::custom().closed_len(1).build();
+
+        recl.force_open();
+        assert_eq!(CircuitState::ForcedOpen, recl.state());
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+
+        recl.reset();
+        assert_eq!(CircuitState::Closed, recl.state());
 
-    /// Wraps a function that may fail, records the result as success or failure.
-    /// Uses default `AnyError` predicate that considers any `Err(_)` as a failure.
-    /// Based on the result, state transition may happen.
-    pub fn call<F, T, E>(&self, f: F) -> Result<T, Error<E>>
-    where
-        F: FnOnce() -> Result<T, E>,
-    {
-        self.call_with(AnyError, f)
+        // The window is fresh after a reset
+        let _ = recl.call(|| Err::<(), ()>(()));
+        recl.reset();
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
     }
 
-    /// Wraps a function that may fail, the custom `predicate` will be used to
-    /// determine whether the result was a success or failure.
-    /// Based on the result, state transition may happen.
-    pub fn call_with<P, F, T, E>(&self, predicate: P, f: F) -> Result<T, Error<E>>
-    where
-        P: ErrorPredicate<E>,
-        F: FnOnce() -> Result<T, E>,
-    {
-        let guard = &epoch::pin();
+    #[test]
+    fn disabled() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .fuse_wait(Duration::from_secs(1))
+            .disabled()
+            .build();
 
-        if !self.call_permitted(guard) {
-            return Err(Error::Rejected);
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), ()>(()));
         }
+        let fuse = AnyError.fuse_on(|_: &()| true);
+        let _ = recl.call_with(fuse, || Err::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
+        assert_eq!(4, recl.metrics().failures);
 
-        match f() {
-            Ok(ok) => {
-                self.on_success(guard);
-                Ok(ok)
-            }
-            Err(err) => {
-                if predicate.is_err(&err) {
-                    self.on_error(guard);
-                } else {
-                    self.on_success(guard);
-                }
-                Err(Error::Inner(err))
-            }
-        }
+        // Manual transitions remain effective, disabling permits all calls again
+        recl.trip();
+        assert_eq!(CircuitState::Open, recl.state());
+        recl.set_disabled(true);
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        // Enforced once enabled back
+        recl.set_disabled(false);
+        assert!(!recl.is_disabled());
+        let _ = recl.call(|| Err::<(), ()>(()));
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
     }
 
-    pub(crate) fn call_permitted(&self, guard: &Guard) -> bool {
-        // Safety: safe because `Shared::null()` is never used.
-        match unsafe { self.state.load(Acquire, guard).deref() } {
-            State::Closed(_) => true,
-            State::HalfOpen(_) => true,
-            State::Open(until) => {
-                if Instant::now() > *until {
-                    self.state.store(
-                        Owned::new(State::HalfOpen(RingBuffer::new(self.half_open_len))),
-                        Release,
-                    );
-                    true
-                } else {
-                    false
-                }
-            }
+    #[test]
+    fn failure_count() {
+        let recl = Recloser::custom().closed_len(100).failure_count(3).build();
+
+        for _ in 0..10 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
         }
+        assert_eq!(CircuitState::Closed, recl.state());
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
     }
 
-    pub(crate) fn on_success(&self, guard: &Guard) {
-        // Safety: safe because `Shared::null()` is never used.
-        match unsafe { self.state.load(Acquire, guard).deref() } {
-            State::Closed(rb) => {
-                rb.set_current(false);
-            }
-            State::HalfOpen(rb) => {
-                let failure_rate = rb.set_current(false);
-                if failure_rate > -1.0 && failure_rate <= self.threshold {
-                    self.state.store(
-                        Owned::new(State::Closed(RingBuffer::new(self.closed_len))),
-                        Release,
-                    );
-                }
-            }
-            State::Open(_) => (),
-        };
+    #[test]
+    fn minimum_calls() {
+        let recl = Recloser::custom()
+            .closed_len(100)
+            .minimum_calls(4)
+            .failure_count(1)
+            .build();
+
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
+
+        let recl = Recloser::custom()
+            .error_rate(0.5)
+            .closed_len(100)
+            .minimum_calls(4)
+            .build();
+
+        for _ in 0..3 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, recl.state());
     }
 
-    pub(crate) fn on_error(&self, guard: &Guard) {
-        // Safety: safe because `Shared::null()` is never used.
-        match unsafe { self.state.load(Acquire, guard).deref() } {
-            State::Closed(rb) | State::HalfOpen(rb) => {
-                let failure_rate = rb.set_current(true);
-                if failure_rate > -1.0 && failure_rate >= self.threshold {
-                    self.state.store(
-                        Owned::new(State::Open(Instant::now() + self.open_wait)),
-                        Release,
-                    );
-                }
-            }
-            State::Open(_) => (),
-        };
+    #[test]
+    fn stale_calls() {
+        let recl = Recloser::custom().closed_len(1).build();
+        let guard = &epoch::pin();
+        let stale = recl.state.load(Acquire);
+
+        recl.trip();
+        sleep(10);
+        recl.reset();
+
+        // Recorded after the `Closed(_)` state it was permitted in was left
+        assert_eq!(-1.0, recl.closed_rate(stale, 1.0, guard));
+        assert_eq!(0, recl.closed_rb(guard).calls());
+
+        let current = recl.state.load(Acquire);
+        assert_eq!(-1.0, recl.closed_rate(current, 1.0, guard));
+        assert_eq!(1, recl.closed_rb(guard).calls());
     }
-}
 
-/// The states a `Recloser` can be in.
-#[derive(Debug)]
-enum State {
-    /// Allows calls until a failure_rate threshold is reached.
-    Closed(RingBuffer),
-    /// Rejects all calls until the future `Instant` is reached.
-    Open(Instant),
-    /// Allows calls until the underlying `RingBuffer` is full,
-    /// then calculates a failure_rate based on which the next transition will happen.
-    HalfOpen(RingBuffer),
-}
+    #[test]
+    fn closed_time_window() {
+        let recl = Recloser::custom()
+            .closed_len(4)
+            .closed_time_window(10, Duration::from_secs(1))
+            .build();
 
-/// A helper struct to build customized `Recloser`.
-#[derive(Debug)]
-pub struct RecloserBuilder {
-    threshold: f32,
-    closed_len: usize,
-    half_open_len: usize,
-    open_wait: Duration,
-}
+        let _ = recl.call(|| Err::<(), ()>(()));
+        let _ = recl.call(|| Err::<(), ()>(()));
+        sleep(10_500);
 
-impl RecloserBuilder {
-    fn new() -> Self {
-        RecloserBuilder {
-            threshold: 0.5,
-            closed_len: 100,
-            half_open_len: 10,
-            open_wait: Duration::from_secs(30),
+        // The failures aged out, 1 out of 4 calls failed within the window
+        for _ in 0..3 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
         }
-    }
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
+        assert_eq!(Some(0.25), recl.snapshot().failure_rate);
+        // Calls are only recorded into the time window
+        assert_eq!(0, recl.closed_rb(&epoch::pin()).calls());
 
-    pub fn error_rate(mut self, threshold: f32) -> Self {
-        self.threshold = threshold;
-        self
+        let _ = recl.call(|| Err::<(), ()>(()));
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
     }
 
-    pub fn closed_len(mut self, closed_len: usize) -> Self {
-        self.closed_len = closed_len;
-        self
+    #[test]
+    fn half_open_ttl() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(10)
+            .open_wait(Duration::from_secs(1))
+            .half_open_ttl(Duration::from_secs(2))
+            .build();
+
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        sleep(1500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::HalfOpen, recl.state());
+
+        sleep(2500);
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
+        assert_eq!(CircuitState::Open, recl.state());
+
+        // Regardless of the order of the builder calls
+        let recl = Recloser::custom()
+            .start_open(Duration::from_secs(1))
+            .half_open_ttl_closes()
+            .half_open_ttl(Duration::from_secs(2))
+            .build();
+        sleep(1500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        sleep(2500);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
+        assert_eq!(CircuitState::Closed, recl.state());
     }
 
-    pub fn half_open_len(mut self, half_open_len: usize) -> Self {
-        self.half_open_len = half_open_len;
-        self
+    #[test]
+    fn half_open_ttl_probes() {
+        let recl = Recloser::custom()
+            .start_open(Duration::from_secs(1))
+            .half_open_max_calls(1)
+            .half_open_ttl(Duration::from_secs(2))
+            .warm_up(Duration::from_secs(3600))
+            .build();
+        let guard = &epoch::pin();
+
+        sleep(1500);
+        let probe = recl.permit(guard);
+        assert!(probe.is_some());
+
+        // Reopening is suppressed while warming up, probes are still limited
+        sleep(2500);
+        assert!(recl.permit(guard).is_none());
+        assert_eq!(CircuitState::HalfOpen, recl.state());
+        drop(probe);
+        assert!(recl.permit(guard).is_some());
     }
 
-    pub fn open_wait(mut self, open_wait: Duration) -> Self {
-        self.open_wait = open_wait;
-        self
+    #[test]
+    fn idle_reset() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .idle_reset(Duration::from_secs(1))
+            .build();
+
+        let _ = recl.call(|| Err::<(), ()>(()));
+        sleep(1500);
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
     }
 
-    pub fn build(self) -> Recloser {
-        Recloser {
-            threshold: self.threshold,
-            closed_len: self.closed_len,
-            half_open_len: self.half_open_len,
-            open_wait: self.open_wait,
-            state: Atomic::new(State::Closed(RingBuffer::new(self.closed_len))),
+    #[test]
+    fn warm_up() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .warm_up(Duration::from_secs(1))
+            .build();
+
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), ()>(()));
         }
-    }
-}
+        assert_eq!(CircuitState::Closed, recl.state());
+        assert_eq!(3, recl.metrics().failures);
 
-impl Default for Recloser {
-    fn default() -> Self {
-        Recloser::custom().build()
+        sleep(1500);
+        let _ = recl.call(|| Err::<(), ()>(()));
+        assert_eq!(CircuitState::Open, recl.state());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::sync::atomic::Ordering::Relaxed;
-    use std::sync::{Arc, Barrier};
-    use std::thread;
+    #[test]
+    fn cooldown() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .cooldown(Duration::from_secs(5))
+            .build();
 
-    use fake_clock::FakeClock;
-    use rand::prelude::*;
+        sleep(5500);
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Open, recl.state());
 
-    use super::*;
+        // Just entered HalfOpen(_) state, cannot close yet
+        sleep(1500);
+        for _ in 0..3 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::HalfOpen, recl.state());
 
-    fn sleep(time: u64) {
-        FakeClock::advance_time(time);
+        sleep(5500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
+
+        // Just entered Closed(_) state, cannot trip yet
+        for _ in 0..3 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(CircuitState::Closed, recl.state());
     }
 
     #[test]
-    fn multi_errors() {
-        let recl = Recloser::custom().closed_len(1).build();
-        let guard = &epoch::pin();
+    fn start_open() {
+        let recl = Recloser::custom()
+            .half_open_len(1)
+            .start_open(Duration::from_secs(1))
+            .build();
 
-        let f = || Err::<(), ()>(());
-        assert!(matches!(recl.call(f), Err(Error::Inner(()))));
-        assert_eq!(true, recl.call_permitted(guard));
+        assert_eq!(CircuitState::Open, recl.state());
+        assert!(matches!(
+            recl.call(|| Ok::<(), ()>(())),
+            Err(Error::Rejected)
+        ));
 
-        let f = || Err::<(), usize>(12);
-        assert!(matches!(recl.call(f), Err(Error::Inner(12))));
-        assert_eq!(false, recl.call_permitted(guard));
+        sleep(1500);
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::HalfOpen, recl.state());
+        let _ = recl.call(|| Ok::<(), ()>(()));
+        assert_eq!(CircuitState::Closed, recl.state());
     }
 
     #[test]
-    fn error_predicate() {
-        let recl = Recloser::custom().closed_len(1).build();
-        let guard = &epoch::pin();
+    fn restore() {
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(2))
+            .build();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        sleep(500);
 
-        let f = || Err::<(), ()>(());
-        let p = |_: &()| false;
+        let restored = Recloser::custom()
+            .half_open_len(1)
+            .restore(recl.snapshot())
+            .build();
+        assert_eq!(CircuitState::Open, restored.state());
+        assert_eq!(1, restored.snapshot().flaps);
+        let retry_after = |recl: &Recloser| recl.time_until_half_open().unwrap().as_millis();
+        assert!((1400..=1500).contains(&retry_after(&restored)));
 
-        assert!(matches!(recl.call_with(p, f), Err(Error::Inner(()))));
-        assert_eq!(true, recl.call_permitted(guard));
+        // The time elapsed since the snapshot was taken is deducted
+        let mut snapshot = recl.snapshot();
+        snapshot.taken_at -= Duration::from_secs(1);
+        let restored = Recloser::custom().restore(snapshot).build();
+        assert!((400..=500).contains(&retry_after(&restored)));
+        snapshot.taken_at -= Duration::from_secs(3600);
+        let restored = Recloser::custom().restore(snapshot).build();
+        assert_eq!(CircuitState::HalfOpen, restored.state());
 
-        assert!(matches!(recl.call_with(p, f), Err(Error::Inner(()))));
-        assert_eq!(true, recl.call_permitted(guard));
+        recl.force_open();
+        let restored = Recloser::custom().restore(recl.snapshot()).build();
+        assert_eq!(CircuitState::ForcedOpen, restored.state());
     }
 
     #[test]
-    fn recloser_correctness() {
+    fn wait_strategy() {
         let recl = Recloser::custom()
-            .error_rate(0.5)
-            .closed_len(2)
-            .half_open_len(2)
-            .open_wait(Duration::from_secs(1))
+            .closed_len(1)
+            .half_open_len(1)
+            .wait_strategy(|ctx: &WaitContext| Duration::from_secs(1 + ctx.reopens as u64))
             .build();
 
-        let guard = &epoch::pin();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+        assert_eq!(State::Open, recl.current_state());
 
-        // Fill the State::Closed ring buffer
+        sleep(1500);
         for _ in 0..2 {
-            assert!(matches!(
-                recl.call(|| Err::<(), ()>(())),
-                Err(Error::Inner(()))
-            ));
-            assert!(matches!(
-                unsafe { &recl.state.load(Relaxed, guard).deref() },
-                State::Closed(_)
-            ));
+            let _ = recl.call(|| Err::<(), ()>(()));
         }
+        assert_eq!(State::Open, recl.current_state());
 
-        // Transition to State::Open on next call
-        assert!(matches!(
-            recl.call(|| Err::<(), ()>(())),
-            Err(Error::Inner(()))
-        ));
-        assert!(matches!(
-            unsafe { &recl.state.load(Relaxed, guard).deref() },
-            State::Open(_)
-        ));
+        // Waits longer after reopening
+        sleep(1500);
         assert!(matches!(
-            recl.call(|| Err::<(), ()>(())),
+            recl.call(|| Ok::<(), ()>(())),
             Err(Error::Rejected)
         ));
+        sleep(1000);
+        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
 
-        // Transition to State::HalfOpen on first call after 1 sec
+        let boxed: BoxedWait = Box::new(DefaultWait(Duration::from_secs(1)));
+        let recl = Recloser::custom()
+            .closed_len(1)
+            .wait_strategy(boxed)
+            .build();
+        for _ in 0..2 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
         sleep(1500);
         assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
-        assert!(matches!(
-            unsafe { &recl.state.load(Relaxed, guard).deref() },
-            State::HalfOpen(_)
-        ));
+    }
 
-        // Fill the State::HalfOpen ring buffer
-        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
-        assert!(matches!(
-            unsafe { &recl.state.load(Relaxed, guard).deref() },
-            State::HalfOpen(_)
-        ));
+    #[test]
+    fn warning_hook() {
+        let warnings = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let w = warnings.clone();
+        let recl = Recloser::custom()
+            .closed_len(10)
+            .on_warning(move |warning| w.lock().unwrap().push(*warning))
+            .build();
 
-        // Transition to State::Closed when failure rate below threshold
-        assert!(matches!(recl.call(|| Ok::<(), ()>(())), Ok(())));
-        assert!(matches!(
-            unsafe { &recl.state.load(Relaxed, guard).deref() },
-            State::Closed(_)
-        ));
+        for _ in 0..10 {
+            let _ = recl.call(|| Ok::<(), ()>(()));
+        }
+        for _ in 0..4 {
+            let _ = recl.call(|| Err::<(), ()>(()));
+        }
+
+        assert_eq!(
+            vec![Warning {
+                failure_rate: 0.4,
+                threshold: 0.5
+            }],
+            *warnings.lock().unwrap()
+        );
     }
 
     #[test]
@@ -332,4 +3504,78 @@ mod tests {
             handle.join().unwrap();
         }
     }
+
+    /// Collects the fields of all the spans and events as `name=value` strings.
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct Fields(std::sync::Mutex<Vec<String>>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for &Fields {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            let field = format!("{}={:?}", field.name(), value);
+            self.0.lock().unwrap().push(field);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for Fields {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut &*self);
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut &*self);
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut &*self);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn trace_calls() {
+        let fields = Arc::new(Fields::default());
+        let recl = Recloser::custom()
+            .name("traced")
+            .closed_len(1)
+            .trace_calls(1)
+            .build();
+
+        tracing::subscriber::with_default(fields.clone(), || {
+            let _ = recl.call(|| {
+                sleep(20);
+                Err::<(), ()>(())
+            });
+            // Not sampled, but the transition is traced
+            let _ = recl.call(|| Err::<(), ()>(()));
+        });
+
+        let fields = fields.0.lock().unwrap();
+        for field in [
+            r#"breaker_name="traced""#,
+            "state=Closed",
+            "outcome=Failure",
+            "latency=0.02",
+            "to=Open",
+        ] {
+            assert!(fields.iter().any(|f| f == field), "{field} in {fields:?}");
+        }
+        assert_eq!(
+            1,
+            fields.iter().filter(|f| f.starts_with("outcome")).count()
+        );
+    }
 }
@@ -0,0 +1,307 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::metrics::HealthStatus;
+use crate::r#async::AsyncRecloser;
+use crate::recloser::{CircuitState, Recloser, Snapshot};
+use crate::wait::WaitStrategy;
+
+/// The operations available on the type erased `Recloser`s of a `Registry`.
+pub(crate) trait Entry: Send + Sync {
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+    fn state(&self) -> CircuitState;
+    fn snapshot(&self) -> Snapshot;
+    fn trip_error(&self) -> Option<String>;
+    fn trip(&self);
+    fn force_open(&self);
+    fn force_close(&self) -> bool;
+    fn reset(&self);
+}
+
+impl<W, P> Entry for Recloser<W, P>
+where
+    W: WaitStrategy + Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn state(&self) -> CircuitState {
+        Recloser::state(self)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Recloser::snapshot(self)
+    }
+
+    fn trip_error(&self) -> Option<String> {
+        Recloser::trip_error(self)
+    }
+
+    fn trip(&self) {
+        Recloser::trip(self)
+    }
+
+    fn force_open(&self) {
+        Recloser::force_open(self)
+    }
+
+    fn force_close(&self) -> bool {
+        Recloser::force_close(self)
+    }
+
+    fn reset(&self) {
+        Recloser::reset(self)
+    }
+}
+
+impl<W, P> From<AsyncRecloser<W, P>> for Arc<Recloser<W, P>> {
+    fn from(recloser: AsyncRecloser<W, P>) -> Self {
+        recloser.inner
+    }
+}
+
+/// A set of named `Recloser`s, that can be inspected and controlled by name
+/// regardless of their type parameters.
+#[derive(Default)]
+pub struct Registry {
+    entries: RwLock<BTreeMap<String, Arc<dyn Entry>>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Returns the process wide `Registry`. With the `inventory` feature, it initially
+    /// contains the `Recloser`s declared with `register_recloser!(...)`.
+    pub fn global() -> Arc<Registry> {
+        static GLOBAL: OnceLock<Arc<Registry>> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| {
+                let registry = Registry::new();
+                #[cfg(feature = "inventory")]
+                for registration in inventory::iter::<Registration> {
+                    (registration.register)(&registry);
+                }
+                Arc::new(registry)
+            })
+            .clone()
+    }
+
+    /// Registers a `Recloser`, or an `AsyncRecloser`, under `name`. Replaces any
+    /// `Recloser` previously registered under the same name.
+    pub fn register<W, P>(&self, name: impl Into<String>, recloser: impl Into<Arc<Recloser<W, P>>>)
+    where
+        W: WaitStrategy + Send + Sync + 'static,
+        P: Send + Sync + 'static,
+    {
+        let recloser: Arc<Recloser<W, P>> = recloser.into();
+        self.write().insert(name.into(), recloser);
+    }
+
+    /// Returns `false` if no `Recloser` was registered under `name`.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.write().remove(name).is_some()
+    }
+
+    /// Returns the names and current states of all registered `Recloser`s,
+    /// sorted by name.
+    pub fn states(&self) -> Vec<(String, CircuitState)> {
+        self.read()
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.state()))
+            .collect()
+    }
+
+    /// Returns the names and `Snapshot`s of all registered `Recloser`s, sorted by name.
+    pub fn snapshots(&self) -> Vec<(String, Snapshot)> {
+        self.read()
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.snapshot()))
+            .collect()
+    }
+
+    /// Returns the worst `HealthStatus` of all registered `Recloser`s, `Healthy` when
+    /// there are none.
+    pub fn health_status(&self) -> HealthStatus {
+        self.read()
+            .values()
+            .map(|entry| HealthStatus::from(entry.state()))
+            .max()
+            .unwrap_or(HealthStatus::Healthy)
+    }
+
+    /// Returns the `Snapshot` of the `Recloser` registered under `name`.
+    pub fn snapshot(&self, name: &str) -> Option<Snapshot> {
+        self.entry(name).map(|entry| entry.snapshot())
+    }
+
+    /// Returns the `Recloser::trip_error()` of the `Recloser` registered under `name`.
+    pub fn trip_error(&self, name: &str) -> Option<String> {
+        self.entry(name)?.trip_error()
+    }
+
+    /// Returns the current state of the `Recloser` registered under `name`.
+    pub fn state(&self, name: &str) -> Option<CircuitState> {
+        self.entry(name).map(|entry| entry.state())
+    }
+
+    /// Same as `Recloser::trip()`, returns `false` if `name` is unknown.
+    pub fn trip(&self, name: &str) -> bool {
+        self.entry(name).map(|entry| entry.trip()).is_some()
+    }
+
+    /// Same as `Recloser::force_open()`, returns `false` if `name` is unknown.
+    pub fn force_open(&self, name: &str) -> bool {
+        self.entry(name).map(|entry| entry.force_open()).is_some()
+    }
+
+    /// Same as `Recloser::force_close()`, returns `false` if `name` is unknown
+    /// or if it was not in `ForcedOpen` state.
+    pub fn force_close(&self, name: &str) -> bool {
+        self.entry(name).is_some_and(|entry| entry.force_close())
+    }
+
+    /// Same as `Recloser::reset()`, returns `false` if `name` is unknown.
+    pub fn reset(&self, name: &str) -> bool {
+        self.entry(name).map(|entry| entry.reset()).is_some()
+    }
+
+    /// Returns the `Recloser` registered under `name`, `None` if `name` is unknown
+    /// or if its type parameters are not `W` and `P`.
+    pub fn get<W, P>(&self, name: &str) -> Option<Arc<Recloser<W, P>>>
+    where
+        W: WaitStrategy + Send + Sync + 'static,
+        P: Send + Sync + 'static,
+    {
+        self.entry(name)?.as_any().downcast().ok()
+    }
+
+    fn entry(&self, name: &str) -> Option<Arc<dyn Entry>> {
+        self.read().get(name).cloned()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, Arc<dyn Entry>>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, Arc<dyn Entry>>> {
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A `Recloser` declared with `register_recloser!(...)`, registered into
+/// `Registry::global()` on first use.
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub struct Registration {
+    register: fn(&Registry),
+}
+
+#[cfg(feature = "inventory")]
+impl Registration {
+    pub const fn new(register: fn(&Registry)) -> Self {
+        Registration { register }
+    }
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(Registration);
+
+/// Declares a `Recloser`, built from a `RecloserBuilder`, that registers itself
+/// into `Registry::global()` under the given name.
+///
+/// ```rust,ignore
+/// use recloser::{register_recloser, Recloser, Registry};
+///
+/// register_recloser!("payments", Recloser::custom().error_rate(0.3));
+///
+/// let payments = Registry::global().get::<_, _>("payments");
+/// ```
+#[cfg(feature = "inventory")]
+#[macro_export]
+macro_rules! register_recloser {
+    ($name:expr, $builder:expr $(,)?) => {
+        $crate::__private::inventory::submit! {
+            $crate::__private::Registration::new(|registry: &$crate::Registry| {
+                let name = ::std::string::String::from($name);
+                let recloser = $builder.name(name.clone()).build();
+                registry.register(name, ::std::sync::Arc::new(recloser))
+            })
+        }
+    };
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.states()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error::AnyError;
+    use crate::wait::{DefaultWait, WaitContext};
+
+    #[cfg(feature = "inventory")]
+    crate::register_recloser!("inventory", Recloser::custom().error_rate(0.3));
+
+    #[cfg(feature = "inventory")]
+    #[test]
+    fn global_registration() {
+        let registry = Registry::global();
+        assert_eq!(Some(CircuitState::Closed), registry.state("inventory"));
+        let recloser = registry.get::<DefaultWait, AnyError>("inventory").unwrap();
+        assert_eq!(Some("inventory"), recloser.name());
+        assert!(registry.get::<DefaultWait, bool>("inventory").is_none());
+    }
+
+    #[test]
+    fn registry_control() {
+        let registry = Registry::new();
+
+        let payments = AsyncRecloser::from(Recloser::default());
+        registry.register("payments", payments.clone());
+        registry.register(
+            "search",
+            Arc::new(
+                Recloser::custom()
+                    .wait_strategy(|_: &WaitContext| Duration::from_secs(1))
+                    .build(),
+            ),
+        );
+
+        assert!(registry.force_open("payments"));
+        assert!(!registry.force_open("unknown"));
+        assert!(matches!(
+            payments.inner.call(|| Ok::<(), ()>(())),
+            Err(crate::Error::Rejected)
+        ));
+        assert_eq!(
+            vec![
+                ("payments".to_string(), CircuitState::ForcedOpen),
+                ("search".to_string(), CircuitState::Closed)
+            ],
+            registry.states()
+        );
+        assert_eq!(HealthStatus::Unhealthy, registry.health_status());
+
+        assert!(registry.force_close("payments"));
+        assert!(!registry.force_close("payments"));
+        assert!(registry.reset("search"));
+        assert_eq!(HealthStatus::Healthy, registry.health_status());
+
+        let payments = registry.get::<DefaultWait, AnyError>("payments").unwrap();
+        assert!(payments.call(|| Ok::<(), ()>(())).is_ok());
+
+        assert!(registry.unregister("search"));
+        assert_eq!(None, registry.state("search"));
+        assert_eq!(Some(CircuitState::Closed), registry.state("payments"));
+    }
+}
@@ -0,0 +1,231 @@
+use std::error::Error as StdError;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+
+use crate::error::ErrorPredicate;
+
+/// An `ErrorPredicate` for type erased error reports (`anyhow::Error`, `eyre::Report`),
+/// that classifies them based on what their chain contains.
+///
+/// Rules are checked in order, the first matching one decides whether the report is
+/// a failure. Reports not matching any rule are failures, unless `otherwise(false)`.
+///
+/// ```rust,ignore
+/// use recloser::{AnyhowPredicate, Recloser};
+///
+/// let predicate = AnyhowPredicate::new()
+///     .ignore::<std::num::ParseIntError>()
+///     .fail_on::<std::io::Error>()
+///     .otherwise(false);
+///
+/// let recloser = Recloser::custom().default_predicate(predicate).build();
+/// ```
+pub struct ReportPredicate<R> {
+    rules: Vec<(Matcher<R>, bool)>,
+    otherwise: bool,
+    _report: PhantomData<fn(&R)>,
+}
+
+type Matcher<R> = fn(&R) -> bool;
+
+/// A `ReportPredicate` for `anyhow::Error`.
+#[cfg(feature = "anyhow")]
+pub type AnyhowPredicate = ReportPredicate<anyhow::Error>;
+
+/// A `ReportPredicate` for `eyre::Report`.
+#[cfg(feature = "eyre")]
+pub type EyrePredicate = ReportPredicate<eyre::Report>;
+
+impl<R: Report> ReportPredicate<R> {
+    pub fn new() -> Self {
+        ReportPredicate {
+            rules: Vec::new(),
+            otherwise: true,
+            _report: PhantomData,
+        }
+    }
+
+    /// Considers reports with an error of type `T` in their chain as failures.
+    pub fn fail_on<T: StdError + 'static>(self) -> Self {
+        self.rule(in_chain::<R, T>, true)
+    }
+
+    /// Does not consider reports with an error of type `T` in their chain as failures.
+    pub fn ignore<T: StdError + 'static>(self) -> Self {
+        self.rule(in_chain::<R, T>, false)
+    }
+
+    /// Considers reports with a context of type `C` as failures, typically a marker
+    /// attached with `.context(...)`.
+    pub fn fail_on_context<C>(self) -> Self
+    where
+        C: Display + Debug + Send + Sync + 'static,
+    {
+        self.rule(R::has::<C>, true)
+    }
+
+    /// Does not consider reports with a context of type `C` as failures.
+    pub fn ignore_context<C>(self) -> Self
+    where
+        C: Display + Debug + Send + Sync + 'static,
+    {
+        self.rule(R::has::<C>, false)
+    }
+
+    /// Whether reports not matching any rule are failures, defaults to `true`.
+    pub fn otherwise(mut self, failure: bool) -> Self {
+        self.otherwise = failure;
+        self
+    }
+
+    fn rule(mut self, matches: Matcher<R>, failure: bool) -> Self {
+        self.rules.push((matches, failure));
+        self
+    }
+}
+
+impl<R: Report> Default for ReportPredicate<R> {
+    fn default() -> Self {
+        ReportPredicate::new()
+    }
+}
+
+impl<R> Clone for ReportPredicate<R> {
+    fn clone(&self) -> Self {
+        ReportPredicate {
+            rules: self.rules.clone(),
+            otherwise: self.otherwise,
+            _report: PhantomData,
+        }
+    }
+}
+
+impl<R> Debug for ReportPredicate<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReportPredicate")
+            .field("rules", &self.rules.len())
+            .field("otherwise", &self.otherwise)
+            .finish()
+    }
+}
+
+impl<R: Report> ErrorPredicate<R> for ReportPredicate<R> {
+    fn is_err(&self, err: &R) -> bool {
+        self.rules
+            .iter()
+            .find(|(matches, _)| matches(err))
+            .map_or(self.otherwise, |(_, failure)| *failure)
+    }
+}
+
+fn in_chain<R: Report, T: StdError + 'static>(report: &R) -> bool {
+    report.any_source(&mut |err| err.is::<T>())
+}
+
+/// Error reports supported by `ReportPredicate`, sealed.
+pub trait Report: sealed::Sealed {
+    #[doc(hidden)]
+    fn has<C>(&self) -> bool
+    where
+        C: Display + Debug + Send + Sync + 'static;
+
+    #[doc(hidden)]
+    fn any_source(&self, f: &mut dyn FnMut(&(dyn StdError + 'static)) -> bool) -> bool;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+#[cfg(feature = "anyhow")]
+impl sealed::Sealed for anyhow::Error {}
+
+#[cfg(feature = "anyhow")]
+impl Report for anyhow::Error {
+    fn has<C>(&self) -> bool
+    where
+        C: Display + Debug + Send + Sync + 'static,
+    {
+        self.downcast_ref::<C>().is_some()
+    }
+
+    fn any_source(&self, f: &mut dyn FnMut(&(dyn StdError + 'static)) -> bool) -> bool {
+        self.chain().any(f)
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl sealed::Sealed for eyre::Report {}
+
+#[cfg(feature = "eyre")]
+impl Report for eyre::Report {
+    fn has<C>(&self) -> bool
+    where
+        C: Display + Debug + Send + Sync + 'static,
+    {
+        self.downcast_ref::<C>().is_some()
+    }
+
+    fn any_source(&self, f: &mut dyn FnMut(&(dyn StdError + 'static)) -> bool) -> bool {
+        self.chain().any(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Retryable;
+
+    impl fmt::Display for Retryable {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("retryable")
+        }
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn anyhow_predicate() {
+        use anyhow::Context;
+
+        let p = AnyhowPredicate::new()
+            .ignore::<io::Error>()
+            .fail_on_context::<Retryable>()
+            .otherwise(false);
+
+        let err = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound)).context("reading");
+        assert!(!p.is_err(&err));
+
+        let err = Err::<(), _>(anyhow::anyhow!("timeout"))
+            .context(Retryable)
+            .unwrap_err();
+        assert!(p.is_err(&err));
+
+        assert!(!p.is_err(&anyhow::anyhow!("other")));
+        assert!(AnyhowPredicate::new().is_err(&anyhow::anyhow!("other")));
+    }
+
+    #[cfg(feature = "eyre")]
+    #[test]
+    fn eyre_predicate() {
+        use eyre::WrapErr;
+
+        let p = EyrePredicate::new().fail_on::<io::Error>().otherwise(false);
+
+        let err = Err::<(), _>(io::Error::from(io::ErrorKind::NotFound))
+            .wrap_err("reading")
+            .unwrap_err();
+        assert!(p.is_err(&err));
+        assert!(!p.is_err(&eyre::eyre!("other")));
+
+        let p = EyrePredicate::new().ignore_context::<Retryable>();
+        let err = Err::<(), _>(eyre::eyre!("timeout"))
+            .wrap_err(Retryable)
+            .unwrap_err();
+        assert!(!p.is_err(&err));
+    }
+}
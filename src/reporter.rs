@@ -0,0 +1,151 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::r#async::AsyncRecloser;
+use crate::recloser::{Recloser, Snapshot};
+use crate::registry::Registry;
+use crate::wait::WaitStrategy;
+
+/// Receives the `Snapshot`s periodically pushed by a reporter task, see
+/// `AsyncRecloser::spawn_reporter(...)` and `Registry::spawn_reporter(...)`.
+///
+/// The `name` is the one a `Recloser` is registered under, `None` when reporting
+/// a single `Recloser`. Reporting should not block, as it runs on the async runtime.
+pub trait Sink: Send + 'static {
+    fn report(&mut self, name: Option<&str>, snapshot: &Snapshot);
+}
+
+impl<F> Sink for F
+where
+    F: FnMut(Option<&str>, &Snapshot) + Send + 'static,
+{
+    fn report(&mut self, name: Option<&str>, snapshot: &Snapshot) {
+        self(name, snapshot)
+    }
+}
+
+impl<W, P> AsyncRecloser<W, P>
+where
+    W: WaitStrategy + Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    /// Spawns a task pushing a `Snapshot` of the `Recloser` to `sink` every `interval`,
+    /// starting immediately. The task ends once the `Recloser` is dropped.
+    pub fn spawn_reporter<S: Sink>(&self, interval: Duration, mut sink: S) -> JoinHandle<()> {
+        let recloser = Arc::downgrade(&self.inner);
+        spawn_ticking(interval, move || match recloser.upgrade() {
+            Some(recloser) => {
+                sink.report(None, &Recloser::snapshot(&recloser));
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+impl Registry {
+    /// Spawns a task pushing the `Snapshot`s of all the registered `Recloser`s to
+    /// `sink` every `interval`, starting immediately. The task ends once the
+    /// `Registry` is dropped.
+    pub fn spawn_reporter<S: Sink>(
+        self: &Arc<Self>,
+        interval: Duration,
+        mut sink: S,
+    ) -> JoinHandle<()> {
+        let registry: Weak<Registry> = Arc::downgrade(self);
+        spawn_ticking(interval, move || match registry.upgrade() {
+            Some(registry) => {
+                for (name, snapshot) in registry.snapshots() {
+                    sink.report(Some(&name), &snapshot);
+                }
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+/// Spawns a task calling `tick` every `interval` until it returns `false`.
+fn spawn_ticking<F>(interval: Duration, mut tick: F) -> JoinHandle<()>
+where
+    F: FnMut() -> bool + Send + 'static,
+{
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            if !tick() {
+                return;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::recloser::CircuitState;
+
+    #[tokio::test(start_paused = true)]
+    async fn push_snapshots() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(2).build());
+        let _ = recloser.inner.call(|| Err::<(), ()>(()));
+        let _ = recloser.inner.call(|| Ok::<(), ()>(()));
+
+        let sink = reports.clone();
+        let handle = recloser.spawn_reporter(
+            Duration::from_secs(1),
+            move |name: Option<&str>, snapshot: &Snapshot| {
+                sink.lock()
+                    .unwrap()
+                    .push((name.map(String::from), *snapshot));
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        {
+            let reports = reports.lock().unwrap();
+            assert_eq!(3, reports.len());
+            assert_eq!(None, reports[0].0);
+            assert_eq!(Some(0.5), reports[0].1.failure_rate);
+        }
+
+        drop(recloser);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(handle.is_finished());
+
+        let registry = Arc::new(Registry::new());
+        registry.register("payments", Arc::new(Recloser::default()));
+        registry.force_open("payments");
+
+        reports.lock().unwrap().clear();
+        let sink = reports.clone();
+        let handle = registry.spawn_reporter(
+            Duration::from_secs(1),
+            move |name: Option<&str>, snapshot: &Snapshot| {
+                sink.lock()
+                    .unwrap()
+                    .push((name.map(String::from), *snapshot));
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        {
+            let reports = reports.lock().unwrap();
+            assert_eq!(1, reports.len());
+            assert_eq!(Some("payments"), reports[0].0.as_deref());
+            assert_eq!(CircuitState::ForcedOpen, reports[0].1.state);
+            assert_eq!(None, reports[0].1.retry_after);
+        }
+
+        drop(registry);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(handle.is_finished());
+    }
+}
@@ -1,77 +1,333 @@
 use std::sync::atomic::{
-    AtomicBool, AtomicUsize,
+    AtomicBool, AtomicU32, AtomicU64, AtomicUsize,
     Ordering::{Acquire, Relaxed, Release},
 };
+use std::time::Duration;
+
+use crate::rolling::Buckets;
+
+/// Weight of a regular failed call, in the fixed point unit of the ring slots.
+const UNIT: u32 = 1000;
 
 /// Records successful and failed calls, calculates failure rate.
-/// A `true` value in the ring represents a call that failed.
-/// Therefore the failure rate is the ratio: card/len.
+/// A non zero value in the ring represents a call that failed, weighing `UNIT` for a
+/// regular failure. Therefore the failure rate is the ratio: card/(len*UNIT), at most 1.
+///
+/// The ring is allocated with a power of two capacity of at least `len`, so that
+/// slots are found by masking an ever increasing `index` instead of wrapping it.
+/// The value leaving the window is the one recorded `len` calls ago.
+///
+/// With a `Decay`, the failure rate is instead weighted by recency within the window.
+///
+/// Its `generation` tells which state the calls are recorded for, so that calls recorded
+/// late for a previous state are dropped once the `RingBuffer` is renewed for a new one.
 #[derive(Debug)]
 pub struct RingBuffer {
     spin_lock: AtomicBool,
+    generation: AtomicU64,
     len: usize,
+    mask: usize,
     card: AtomicUsize,
+    decay: Option<Decay>,
     filling: AtomicUsize,
-    ring: Box<[AtomicBool]>,
+    ring: Box<[AtomicU32]>,
     index: AtomicUsize,
 }
 
 impl RingBuffer {
     pub fn new(len: usize) -> Self {
-        let mut buf = Vec::with_capacity(len);
+        let capacity = len.next_power_of_two();
+        let mut buf = Vec::with_capacity(capacity);
 
-        for _ in 0..len {
-            buf.push(AtomicBool::new(false));
+        for _ in 0..capacity {
+            buf.push(AtomicU32::new(0));
         }
 
         RingBuffer {
             spin_lock: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
             len,
+            mask: capacity - 1,
             card: AtomicUsize::new(0),
+            decay: None,
             filling: AtomicUsize::new(0),
             ring: buf.into_boxed_slice(),
             index: AtomicUsize::new(0),
         }
     }
 
+    /// Weights the calls of the window so that a call recorded `half_life` calls ago
+    /// contributes half as much to the failure rate as the latest one.
+    pub fn decayed(mut self, half_life: f32) -> Self {
+        self.decay = Some(Decay::new(half_life, self.len));
+        self
+    }
+
+    /// Records the calls for `generation` from the start.
+    pub fn with_generation(self, generation: u64) -> Self {
+        self.generation.store(generation, Relaxed);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns an empty `RingBuffer` of `len` calls, decayed as this one if it is.
+    pub fn with_len(&self, len: usize) -> Self {
+        RingBuffer {
+            decay: self.decay.as_ref().map(|decay| decay.with_len(len)),
+            ..RingBuffer::new(len)
+        }
+    }
+
+    /// Returns the number of calls recorded so far, at most `len`.
+    pub fn calls(&self) -> usize {
+        self.filling.load(Relaxed)
+    }
+
+    /// Returns the number of failed calls of the window, weighted.
+    pub fn failures(&self) -> f32 {
+        self.card.load(Relaxed) as f32 / UNIT as f32
+    }
+
+    #[cfg(test)]
     pub fn set_current(&self, val_new: bool) -> f32 {
+        self.record(to_int(val_new), None, None).unwrap_or(-1.0)
+    }
+
+    /// Same as `set_current(true)`, but the failed call counts as `weight` failures,
+    /// for instance `3.0` for a timeout or `0.5` for a throttled call.
+    pub fn set_weighted(&self, weight: f32) -> f32 {
+        self.record(to_weight(weight), None, None).unwrap_or(-1.0)
+    }
+
+    /// Same as `set_weighted(...)`, but only calculates the failure rate once every
+    /// `every` calls, over the last `len` calls at most. With `every == len` it is
+    /// calculated once per window, that is when all the recorded calls have been renewed.
+    /// A zero `weight` records a successful call.
+    #[cfg(test)]
+    pub fn set_weighted_every(&self, weight: f32, every: usize) -> f32 {
+        self.record(to_weight(weight), Some(every), None)
+            .unwrap_or(-1.0)
+    }
+
+    /// Same as `set_weighted(...)`, or `set_weighted_every(...)` when `every` is set, but
+    /// only records the call if it is still of `generation`, returns `None` otherwise.
+    pub fn set_weighted_for(
+        &self,
+        generation: u64,
+        weight: f32,
+        every: Option<usize>,
+    ) -> Option<f32> {
+        self.record(to_weight(weight), every, Some(generation))
+    }
+
+    fn record(&self, val_new: u32, every: Option<usize>, generation: Option<u64>) -> Option<f32> {
         while self.spin_lock.swap(true, Acquire) {
             std::hint::spin_loop();
         }
 
+        if generation.is_some_and(|generation| generation != self.generation.load(Relaxed)) {
+            self.spin_lock.store(false, Release);
+            return None;
+        }
+
         let i = self.index.load(Relaxed);
-        let j = if i == self.len - 1 { 0 } else { i + 1 };
+        let j = i.wrapping_add(1);
 
-        let val_old = self.ring[i].load(Relaxed);
+        // Slots of the last `capacity - len` calls are still zero while filling
+        let val_old = self.ring[i.wrapping_sub(self.len) & self.mask].load(Relaxed);
 
         let card_old = self.card.load(Relaxed);
-        let card_new = card_old - to_int(val_old) + to_int(val_new);
+        let card_new = card_old - val_old as usize + val_new as usize;
+        if let Some(decay) = &self.decay {
+            decay.record(val_old, val_new);
+        }
 
-        let rate = if self.filling.load(Relaxed) == self.len {
-            card_new as f32 / self.len as f32
-        } else {
+        let filling = self.filling.load(Relaxed);
+        if filling < self.len {
             self.filling.fetch_add(1, Relaxed);
-            -1.0
+        }
+
+        let rate = match every {
+            None if filling == self.len => self.rate(card_new, self.len),
+            Some(every) if i >= every && i.is_multiple_of(every) => {
+                self.rate(card_new, (i + 1).min(self.len))
+            }
+            _ => -1.0,
         };
 
-        self.ring[i].store(val_new, Relaxed);
+        self.ring[i & self.mask].store(val_new, Relaxed);
         self.index.store(j, Relaxed);
         self.card.store(card_new, Relaxed);
 
+        self.spin_lock.store(false, Release);
+        Some(rate)
+    }
+
+    /// Returns the failure rate of the calls recorded so far, `None` if there are none.
+    pub fn failure_rate(&self) -> Option<f32> {
+        while self.spin_lock.swap(true, Acquire) {
+            std::hint::spin_loop();
+        }
+
+        let filling = self.filling.load(Relaxed);
+        let card = self.card.load(Relaxed);
+        let rate = (filling > 0).then(|| self.rate(card, filling));
+
         self.spin_lock.store(false, Release);
         rate
     }
+
+    /// The failure rate of the last `calls` calls, whose failures weigh `card`.
+    fn rate(&self, card: usize, calls: usize) -> f32 {
+        match &self.decay {
+            Some(decay) => decay.rate(calls),
+            None => (card as f32 / (calls * UNIT as usize) as f32).min(1.0),
+        }
+    }
+
+    /// Clears all recorded calls, as if the `RingBuffer` was newly created.
+    pub fn reset(&self) {
+        self.renew(self.generation.load(Relaxed));
+    }
+
+    /// Same as `reset()`, the calls are then recorded for `generation`.
+    pub fn renew(&self, generation: u64) {
+        while self.spin_lock.swap(true, Acquire) {
+            std::hint::spin_loop();
+        }
+
+        self.generation.store(generation, Relaxed);
+        for b in self.ring.iter() {
+            b.store(0, Relaxed);
+        }
+        self.card.store(0, Relaxed);
+        if let Some(decay) = &self.decay {
+            decay.sum.store(0, Relaxed);
+        }
+        self.filling.store(0, Relaxed);
+        self.index.store(0, Relaxed);
+
+        self.spin_lock.store(false, Release);
+    }
+}
+
+/// Exponentially decaying weights of the calls of a `RingBuffer` window, the latest
+/// call weighs `1` and each older one `factor` times the next one.
+#[derive(Debug)]
+struct Decay {
+    factor: f64,
+    /// Weight of the call leaving the window, that is `factor^len`.
+    tail: f64,
+    /// Weighted count of the failed calls of the window, as `f64` bits.
+    sum: AtomicU64,
+}
+
+impl Decay {
+    fn new(half_life: f32, len: usize) -> Self {
+        let factor = 0.5f64.powf(1.0 / f64::from(half_life.max(f32::MIN_POSITIVE)));
+        Decay {
+            factor,
+            tail: factor.powi(len as i32),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    fn with_len(&self, len: usize) -> Self {
+        Decay {
+            factor: self.factor,
+            tail: self.factor.powi(len as i32),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    /// Must be called under the spin lock of the `RingBuffer`.
+    fn record(&self, val_old: u32, val_new: u32) {
+        let (val_old, val_new) = (unit(val_old), unit(val_new));
+        let sum = f64::from_bits(self.sum.load(Relaxed));
+        let sum = self.factor * sum + val_new - self.tail * val_old;
+        self.sum.store(sum.max(0.0).to_bits(), Relaxed);
+    }
+
+    /// The weighted failure rate of the last `calls` calls.
+    fn rate(&self, calls: usize) -> f32 {
+        let weight = if self.factor < 1.0 {
+            (1.0 - self.factor.powi(calls as i32)) / (1.0 - self.factor)
+        } else {
+            calls as f64
+        };
+        (f64::from_bits(self.sum.load(Relaxed)) / weight).min(1.0) as f32
+    }
+}
+
+/// Records calls into `buckets` rotating time buckets, so that old calls age out one
+/// bucket at a time rather than one call at a time. Times are in nanoseconds, as given
+/// by the `Recloser` clock, and failures weigh `UNIT` as in a `RingBuffer`.
+#[derive(Debug)]
+pub struct TimeWindow {
+    buckets: Buckets,
 }
 
+impl TimeWindow {
+    pub fn new(buckets: usize, width: Duration) -> Self {
+        TimeWindow {
+            buckets: Buckets::new(buckets, width),
+        }
+    }
+
+    /// Records a call made at `now`, failed when `weight` is not zero, returns the failure
+    /// rate over the window once it holds at least `min_calls` calls or `-1.0`.
+    pub fn set_weighted(&self, now: u64, weight: f32, min_calls: usize) -> f32 {
+        self.buckets.record(now, u64::from(to_weight(weight)));
+        match self.buckets.totals(now) {
+            (calls, card) if calls >= min_calls.max(1) as u64 => rate(calls, card),
+            _ => -1.0,
+        }
+    }
+
+    /// Returns the failure rate over the window at `now` along with its number of calls.
+    pub fn stats(&self, now: u64) -> (Option<f32>, usize) {
+        match self.buckets.totals(now) {
+            (0, _) => (None, 0),
+            (calls, card) => (Some(rate(calls, card)), calls as usize),
+        }
+    }
+
+    /// Returns the number of failed calls of the window at `now`, weighted.
+    pub fn failures(&self, now: u64) -> f32 {
+        self.buckets.totals(now).1 as f32 / UNIT as f32
+    }
+
+    pub fn reset(&self) {
+        self.buckets.reset();
+    }
+}
+
+fn rate(calls: u64, card: u64) -> f32 {
+    (card as f32 / (calls * u64::from(UNIT)) as f32).min(1.0)
+}
+
+#[cfg(test)]
 #[inline(always)]
-fn to_int(b: bool) -> usize {
+fn to_int(b: bool) -> u32 {
     if b {
-        1
+        UNIT
     } else {
         0
     }
 }
 
+/// Converts a failure weight to the fixed point unit of the ring slots.
+fn to_weight(weight: f32) -> u32 {
+    (weight.max(0.0) * UNIT as f32).round() as u32
+}
+
+fn unit(val: u32) -> f64 {
+    f64::from(val) / f64::from(UNIT)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Barrier};
@@ -107,16 +363,143 @@ mod tests {
             handle.join().unwrap();
         }
 
+        let index = rb.index.load(Relaxed);
+        assert_eq!(num_threads * loop_len * 3, index);
         assert_eq!(
             rb.card.load(Relaxed),
-            rb.ring
-                .iter()
-                .map(|b| to_int(b.load(Relaxed)))
-                .fold(0, |acc, i| acc + i)
-        );
-        assert_eq!(
-            (num_threads * loop_len * 3) % rb_len,
-            rb.index.load(Relaxed)
+            (index - rb_len..index)
+                .map(|i| rb.ring[i & rb.mask].load(Relaxed) as usize)
+                .sum::<usize>()
         );
     }
+
+    #[test]
+    fn time_window() {
+        let second = 1_000_000_000;
+        let window = TimeWindow::new(3, Duration::from_secs(1));
+
+        assert_eq!(-1.0, window.set_weighted(0, 1.0, 2));
+        assert_eq!(0.5, window.set_weighted(second, 0.0, 2));
+        assert_eq!((Some(0.5), 2), window.stats(2 * second));
+
+        // The first bucket ages out on its own
+        assert_eq!(0.0, window.set_weighted(3 * second, 0.0, 2));
+        assert_eq!((None, 0), window.stats(6 * second));
+
+        window.set_weighted(6 * second, 1.5, 2);
+        assert_eq!(1.5, window.failures(6 * second));
+        window.reset();
+        assert_eq!((None, 0), window.stats(6 * second));
+    }
+
+    #[test]
+    fn ring_buffer_window() {
+        let rb = RingBuffer::new(3);
+        assert_eq!(4, rb.ring.len());
+
+        for _ in 0..3 {
+            assert_eq!(-1.0, rb.set_current(true));
+        }
+        assert!((rb.set_current(false) - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!((rb.set_current(false) - 1.0 / 3.0).abs() < f32::EPSILON);
+        assert_eq!(0.0, rb.set_current(false));
+        assert_eq!(0.0, rb.set_current(false));
+        assert!((rb.set_current(true) - 1.0 / 3.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn ring_buffer_batched() {
+        let rb = RingBuffer::new(2);
+
+        assert_eq!(-1.0, rb.set_weighted_every(1.0, 2));
+        assert_eq!(-1.0, rb.set_weighted_every(1.0, 2));
+        assert_eq!(0.5, rb.set_weighted_every(0.0, 2));
+        assert_eq!(-1.0, rb.set_weighted_every(0.0, 2));
+        assert_eq!(0.0, rb.set_weighted_every(0.0, 2));
+    }
+
+    #[test]
+    fn ring_buffer_every() {
+        let rb = RingBuffer::new(8);
+
+        for _ in 0..3 {
+            assert_eq!(-1.0, rb.set_weighted_every(1.0, 3));
+        }
+        assert_eq!(3.0 / 4.0, rb.set_weighted_every(0.0, 3));
+        for _ in 0..2 {
+            assert_eq!(-1.0, rb.set_weighted_every(0.0, 3));
+        }
+        assert_eq!(3.0 / 7.0, rb.set_weighted_every(0.0, 3));
+        for _ in 0..2 {
+            assert_eq!(-1.0, rb.set_weighted_every(0.0, 3));
+        }
+        assert_eq!(1.0 / 8.0, rb.set_weighted_every(0.0, 3));
+    }
+
+    #[test]
+    fn ring_buffer_decayed() {
+        let rb = RingBuffer::new(4).decayed(1.0);
+
+        rb.set_current(true);
+        for _ in 0..3 {
+            rb.set_current(false);
+        }
+        // Weights are 1/8, 1/4, 1/2 and 1, from the oldest call
+        assert_eq!(Some(1.0 / 15.0), rb.failure_rate());
+
+        // The failed call left the window
+        assert_eq!(0.0, rb.set_current(false));
+        assert_eq!(8.0 / 15.0, rb.set_current(true));
+
+        rb.reset();
+        assert_eq!(None, rb.failure_rate());
+        assert_eq!(-1.0, rb.set_current(false));
+    }
+
+    #[test]
+    fn ring_buffer_failure_rate() {
+        let rb = RingBuffer::new(4);
+        assert_eq!(None, rb.failure_rate());
+
+        rb.set_current(true);
+        rb.set_current(false);
+        assert_eq!(Some(0.5), rb.failure_rate());
+
+        for _ in 0..4 {
+            rb.set_current(false);
+        }
+        assert_eq!(Some(0.0), rb.failure_rate());
+    }
+
+    #[test]
+    fn ring_buffer_weighted() {
+        let rb = RingBuffer::new(4);
+
+        rb.set_weighted(0.5);
+        for _ in 0..3 {
+            rb.set_current(false);
+        }
+        assert_eq!(Some(0.125), rb.failure_rate());
+        assert_eq!(0.75, rb.set_weighted(3.0));
+        assert_eq!(1.0, rb.set_weighted(3.0));
+
+        for _ in 0..2 {
+            rb.set_current(false);
+        }
+        assert_eq!(0.75, rb.set_current(false));
+    }
+
+    #[test]
+    fn ring_buffer_reset() {
+        let rb = RingBuffer::new(2);
+
+        for _ in 0..3 {
+            rb.set_current(true);
+        }
+        rb.reset();
+
+        assert_eq!(-1.0, rb.set_current(false));
+        assert_eq!(-1.0, rb.set_current(false));
+        assert_eq!(0.5, rb.set_current(true));
+    }
 }
@@ -0,0 +1,191 @@
+use std::sync::atomic::{
+    AtomicU64,
+    Ordering::{Acquire, Relaxed, Release, SeqCst},
+};
+use std::time::Duration;
+
+/// Number of buckets a `RollingCounter` window is divided into.
+const BUCKETS: usize = 10;
+
+/// Index of a bucket being cleared for a newer index, see `Buckets::record(...)`.
+const ROTATING: u64 = u64::MAX;
+
+/// Rotating time buckets of calls and weighted failures, so that old calls age out
+/// one bucket at a time. Times are in nanoseconds, as given by the `Recloser` clock.
+///
+/// A bucket is only cleared by the thread moving it to a newer index, once the threads
+/// adding to the counts of the older one are done. Other threads wait for the newer
+/// index to be published rather than adding to the counts of the older one.
+#[derive(Debug)]
+pub(crate) struct Buckets {
+    width: u64,
+    buckets: Box<[Bucket]>,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    index: AtomicU64,
+    /// Threads adding to the counts, that a rotation waits for.
+    writers: AtomicU64,
+    calls: AtomicU64,
+    card: AtomicU64,
+}
+
+impl Buckets {
+    pub(crate) fn new(buckets: usize, width: Duration) -> Self {
+        Buckets {
+            width: (width.as_nanos() as u64).max(1),
+            buckets: (0..buckets.max(1)).map(|_| Bucket::default()).collect(),
+        }
+    }
+
+    /// Records a call made at `now`, whose failure weighs `card`.
+    pub(crate) fn record(&self, now: u64, card: u64) {
+        let index = now / self.width;
+        let bucket = &self.buckets[(index % self.buckets.len() as u64) as usize];
+
+        loop {
+            match bucket.index.load(Acquire) {
+                ROTATING => std::hint::spin_loop(),
+                current if current == index => {
+                    bucket.writers.fetch_add(1, SeqCst);
+                    // Rotations started since are waiting for this thread
+                    let rotated = bucket.index.load(SeqCst) != index;
+                    if !rotated {
+                        bucket.calls.fetch_add(1, Relaxed);
+                        bucket.card.fetch_add(card, Relaxed);
+                    }
+                    bucket.writers.fetch_sub(1, Release);
+                    if !rotated {
+                        return;
+                    }
+                }
+                // Recorded late by a thread that read the clock earlier, too old
+                current if current > index => return,
+                current => {
+                    if bucket
+                        .index
+                        .compare_exchange(current, ROTATING, SeqCst, Relaxed)
+                        .is_ok()
+                    {
+                        while bucket.writers.load(Acquire) > 0 {
+                            std::hint::spin_loop();
+                        }
+                        bucket.calls.store(0, Relaxed);
+                        bucket.card.store(0, Relaxed);
+                        bucket.index.store(index, Release);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the calls and the weighted failures of the window at `now`.
+    pub(crate) fn totals(&self, now: u64) -> (u64, u64) {
+        let index = now / self.width;
+        let len = self.buckets.len() as u64;
+
+        self.buckets
+            .iter()
+            .filter(|b| {
+                let i = b.index.load(Acquire);
+                i != ROTATING && i <= index && i + len > index
+            })
+            .fold((0, 0), |(calls, card), b| {
+                (calls + b.calls.load(Relaxed), card + b.card.load(Relaxed))
+            })
+    }
+
+    pub(crate) fn reset(&self) {
+        for b in self.buckets.iter() {
+            b.calls.store(0, Relaxed);
+            b.card.store(0, Relaxed);
+        }
+    }
+}
+
+/// Counts calls and failures over a rolling time window, divided into buckets
+/// so that old samples age out progressively.
+#[derive(Debug)]
+pub(crate) struct RollingCounter {
+    buckets: Buckets,
+}
+
+impl RollingCounter {
+    pub(crate) fn new(window: Duration) -> Self {
+        RollingCounter {
+            buckets: Buckets::new(BUCKETS, window / BUCKETS as u32),
+        }
+    }
+
+    pub(crate) fn record(&self, now: u64, failed: bool) {
+        self.buckets.record(now, failed as u64);
+    }
+
+    /// Returns the calls and failures counted over the window at `now`.
+    pub(crate) fn totals(&self, now: u64) -> (u64, u64) {
+        self.buckets.totals(now)
+    }
+
+    /// Returns the failure rate over the window at `now`, `None` if no calls were counted.
+    pub(crate) fn failure_rate(&self, now: u64) -> Option<f32> {
+        match self.totals(now) {
+            (0, _) => None,
+            (calls, failures) => Some(failures as f32 / calls as f32),
+        }
+    }
+
+    /// Clears all counted calls.
+    pub(crate) fn reset(&self) {
+        self.buckets.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    #[test]
+    fn rolling_counter() {
+        let counter = RollingCounter::new(Duration::from_secs(10));
+        assert_eq!(None, counter.failure_rate(0));
+
+        counter.record(0, true);
+        counter.record(0, false);
+        assert_eq!((2, 1), counter.totals(0));
+
+        counter.record(5 * SECOND, false);
+        counter.record(5 * SECOND, false);
+        assert_eq!(Some(0.25), counter.failure_rate(5 * SECOND));
+
+        // The first bucket ages out
+        assert_eq!((2, 0), counter.totals(10 * SECOND));
+        assert_eq!((0, 0), counter.totals(15 * SECOND));
+
+        // Late records of an older bucket are dropped
+        counter.record(16 * SECOND, true);
+        counter.record(SECOND, true);
+        assert_eq!((1, 1), counter.totals(16 * SECOND));
+    }
+
+    #[test]
+    fn concurrent_buckets() {
+        let buckets = std::sync::Arc::new(Buckets::new(4, Duration::from_nanos(10)));
+        let threads = (0..4)
+            .map(|_| {
+                let buckets = buckets.clone();
+                std::thread::spawn(move || {
+                    for now in 0..1000 {
+                        buckets.record(now, 1);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!((160, 160), buckets.totals(999));
+    }
+}
@@ -0,0 +1,117 @@
+use tarpc::client::stub::Stub;
+use tarpc::client::RpcError;
+use tarpc::context;
+
+use crate::error::{AnyError, Error, ErrorPredicate, RejectedError, Timeouts};
+use crate::r#async::AsyncRecloser;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+/// A `tarpc` `Stub` whose calls are all guarded by the same `AsyncRecloser`, classifying
+/// errors with its default predicate, such as `Timeouts` to only consider exceeded
+/// deadlines as failures.
+///
+/// Rejected calls fail with a `RpcError::Send(_)` holding a `RejectedError`. Generated
+/// clients are built from it, so that every method of the service is guarded.
+///
+/// ```rust,ignore
+/// use recloser::{AsyncRecloser, GuardedStub, Recloser, Timeouts};
+///
+/// let recloser = AsyncRecloser::from(Recloser::custom().default_predicate(Timeouts).build());
+/// let client = WorldClient::from(GuardedStub::new(channel, recloser));
+/// ```
+#[derive(Debug)]
+pub struct GuardedStub<S, W = DefaultWait, P = AnyError> {
+    stub: S,
+    recloser: AsyncRecloser<W, P>,
+}
+
+impl<S: Clone, W, P> Clone for GuardedStub<S, W, P> {
+    fn clone(&self) -> Self {
+        GuardedStub {
+            stub: self.stub.clone(),
+            recloser: self.recloser.clone(),
+        }
+    }
+}
+
+impl<S, W, P> GuardedStub<S, W, P> {
+    pub fn new(stub: S, recloser: AsyncRecloser<W, P>) -> Self {
+        GuardedStub { stub, recloser }
+    }
+
+    pub fn recloser(&self) -> &AsyncRecloser<W, P> {
+        &self.recloser
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.stub
+    }
+}
+
+impl<S, W, P> Stub for GuardedStub<S, W, P>
+where
+    S: Stub,
+    W: WaitStrategy,
+    P: ErrorPredicate<RpcError>,
+{
+    type Req = S::Req;
+    type Resp = S::Resp;
+
+    async fn call(&self, ctx: context::Context, request: S::Req) -> Result<S::Resp, RpcError> {
+        match self.recloser.call(self.stub.call(ctx, request)).await {
+            Ok(resp) => Ok(resp),
+            Err(Error::Inner(err)) => Err(err),
+            Err(Error::Rejected) => Err(RpcError::Send(Box::new(RejectedError))),
+        }
+    }
+}
+
+impl ErrorPredicate<RpcError> for Timeouts {
+    fn is_err(&self, err: &RpcError) -> bool {
+        matches!(err, RpcError::DeadlineExceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+    use super::*;
+    use crate::recloser::{CircuitState, Recloser};
+
+    struct Slow(AtomicBool);
+
+    impl Stub for Slow {
+        type Req = String;
+        type Resp = String;
+
+        async fn call(&self, _ctx: context::Context, request: String) -> Result<String, RpcError> {
+            if self.0.load(Relaxed) {
+                Err(RpcError::DeadlineExceeded)
+            } else {
+                Ok(request)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn guarded_stub() {
+        let recloser = Recloser::custom()
+            .default_predicate(Timeouts)
+            .closed_len(1)
+            .build();
+        let stub = GuardedStub::new(Slow(AtomicBool::new(true)), AsyncRecloser::from(recloser));
+
+        for _ in 0..2 {
+            let res = stub.call(context::current(), "hello".into()).await;
+            assert!(matches!(res, Err(RpcError::DeadlineExceeded)));
+        }
+        assert_eq!(CircuitState::Open, stub.recloser().state());
+
+        stub.inner().0.store(false, Relaxed);
+        let res = stub.call(context::current(), "hello".into()).await;
+        assert!(matches!(res, Err(RpcError::Send(err)) if err.is::<RejectedError>()));
+
+        assert!(!Timeouts.is_err(&RpcError::Shutdown));
+    }
+}
@@ -0,0 +1,107 @@
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "http")]
+use crate::error::{Classifier, Outcome};
+
+/// Classifies HTTP status codes as failures, by default `5xx` and `429 Too Many Requests`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusClassifier {
+    failures: Vec<RangeInclusive<u16>>,
+}
+
+impl Default for StatusClassifier {
+    fn default() -> Self {
+        StatusClassifier {
+            failures: vec![429..=429, 500..=599],
+        }
+    }
+}
+
+impl StatusClassifier {
+    pub fn new() -> Self {
+        StatusClassifier::default()
+    }
+
+    /// A classifier that does not consider any status code as a failure, to be
+    /// configured with `fail_on(...)`.
+    pub fn none() -> Self {
+        StatusClassifier {
+            failures: Vec::new(),
+        }
+    }
+
+    /// Also considers the status codes within `range` as failures.
+    pub fn fail_on(mut self, range: RangeInclusive<u16>) -> Self {
+        self.failures.push(range);
+        self
+    }
+
+    pub fn is_failure(&self, status: u16) -> bool {
+        self.failures.iter().any(|range| range.contains(&status))
+    }
+}
+
+/// With the `http` feature, responses whose status is a failure are classified as
+/// such even though they are `Ok(_)`, transport errors always are failures.
+#[cfg(feature = "http")]
+impl<B, E> Classifier<http::Response<B>, E> for StatusClassifier {
+    fn classify(&self, res: &Result<http::Response<B>, E>) -> Outcome {
+        self.classify_status(res.as_ref().ok().map(http::Response::status))
+    }
+}
+
+#[cfg(feature = "http")]
+impl<E> Classifier<http::StatusCode, E> for StatusClassifier {
+    fn classify(&self, res: &Result<http::StatusCode, E>) -> Outcome {
+        self.classify_status(res.as_ref().ok().copied())
+    }
+}
+
+#[cfg(feature = "http")]
+impl StatusClassifier {
+    fn classify_status(&self, status: Option<http::StatusCode>) -> Outcome {
+        match status {
+            Some(status) if !self.is_failure(status.as_u16()) => Outcome::Success,
+            _ => Outcome::Failure,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_classifier() {
+        let classifier = StatusClassifier::new();
+        assert!(classifier.is_failure(503));
+        assert!(classifier.is_failure(429));
+        assert!(!classifier.is_failure(404));
+        assert!(!classifier.is_failure(200));
+
+        let classifier = StatusClassifier::none().fail_on(400..=499);
+        assert!(classifier.is_failure(404));
+        assert!(!classifier.is_failure(503));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn classify_responses() {
+        use crate::recloser::Recloser;
+
+        let classifier = StatusClassifier::new();
+        let response =
+            |status: u16| Ok::<_, ()>(http::Response::builder().status(status).body(()).unwrap());
+        assert_eq!(Outcome::Failure, classifier.classify(&response(503)));
+        assert_eq!(Outcome::Success, classifier.classify(&response(404)));
+        assert_eq!(
+            Outcome::Failure,
+            classifier.classify(&Err::<http::StatusCode, _>(()))
+        );
+
+        let recloser = Recloser::default();
+        let res = recloser.call_classified(classifier, || response(429));
+        assert_eq!(http::StatusCode::TOO_MANY_REQUESTS, res.unwrap().status());
+        assert_eq!(1, recloser.metrics().failures);
+    }
+}
@@ -0,0 +1,333 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use crossbeam_epoch::{self as epoch, Guard};
+use futures_core::Stream;
+use pin_project::{pin_project, pinned_drop};
+use tokio::time::Sleep;
+
+use crate::error::{AnyError, ErrorPredicate, Outcome};
+use crate::r#async::AsyncRecloser;
+use crate::recloser::Recloser;
+use crate::subscription::{Overflow, Receiver};
+use crate::supervisor::MIN_BACKOFF;
+use crate::wait::{DefaultWait, WaitStrategy};
+
+impl<W: WaitStrategy, P> AsyncRecloser<W, P> {
+    /// Groups the items of `stream` into windows of `size` items, each window being
+    /// recorded as a single call, classified by the default predicate after its worst
+    /// `Err(_)`: blowing the fuse if any item is a fuse error, otherwise a failure of the
+    /// heaviest weight if any item is a failure. A window of ignored errors only is not
+    /// recorded, ignored errors are otherwise not taken into account.
+    ///
+    /// A new window only starts when calls are permitted, otherwise the stream pauses
+    /// until the `Recloser` leaves `Open(_)` state, it is never terminated.
+    pub fn windowed<S, T, E>(&self, stream: S, size: usize) -> Windowed<S, W, P>
+    where
+        S: Stream<Item = Result<T, E>>,
+        P: ErrorPredicate<E>,
+    {
+        Windowed {
+            recloser: self.clone(),
+            stream,
+            size: size.max(1),
+            window: None,
+            pause: None,
+        }
+    }
+
+    /// Returns a `Stream` of the transitions happening from now on. Same as
+    /// `Recloser::subscribe(...)`, buffering up to 64 events and dropping the oldest.
+    pub fn events(&self) -> Receiver {
+        self.inner.subscribe(EVENTS_CAPACITY, Overflow::DropOldest)
+    }
+}
+
+/// Number of events buffered by `AsyncRecloser::events()`.
+const EVENTS_CAPACITY: usize = 64;
+
+/// Custom `Stream` returned by `AsyncRecloser::windowed(...)`.
+#[pin_project(PinnedDrop)]
+pub struct Windowed<S, W = DefaultWait, P = AnyError> {
+    recloser: AsyncRecloser<W, P>,
+    #[pin]
+    stream: S,
+    size: usize,
+    /// The current window, `None` until it is permitted.
+    window: Option<Window>,
+    pause: Option<Pin<Box<Sleep>>>,
+}
+
+struct Window {
+    /// Items seen so far.
+    seen: usize,
+    /// Worst classification of the items seen so far.
+    verdict: Verdict,
+    /// Slot taken in `HalfOpen(_)` state.
+    slot: Option<u64>,
+}
+
+/// Classification of a window, from the mildest to the worst.
+enum Verdict {
+    Ignored,
+    Success,
+    Failure {
+        weight: f32,
+        category: Option<&'static str>,
+        description: Option<String>,
+    },
+    Fuse {
+        description: Option<String>,
+    },
+}
+
+impl Verdict {
+    /// Takes `item` into account, as classified by `predicate`.
+    fn observe<T, E, P: ErrorPredicate<E>>(&mut self, predicate: &P, item: &Result<T, E>) {
+        let err = match item {
+            Ok(_) => {
+                if let Verdict::Ignored = self {
+                    *self = Verdict::Success;
+                }
+                return;
+            }
+            Err(err) => err,
+        };
+        if predicate.is_ignored(err) {
+            return;
+        }
+        if predicate.is_fuse(err) {
+            if !matches!(self, Verdict::Fuse { .. }) {
+                let description = predicate.describe(err);
+                *self = Verdict::Fuse { description };
+            }
+        } else if predicate.is_err(err) {
+            let weight = predicate.weight(err);
+            let heavier = match self {
+                Verdict::Ignored | Verdict::Success => true,
+                Verdict::Failure { weight: w, .. } => weight > *w,
+                Verdict::Fuse { .. } => false,
+            };
+            if heavier {
+                *self = Verdict::Failure {
+                    weight,
+                    category: predicate.category(err),
+                    description: predicate.describe(err),
+                };
+            }
+        } else if let Verdict::Ignored = self {
+            *self = Verdict::Success;
+        }
+    }
+}
+
+#[pinned_drop]
+impl<S, W, P> PinnedDrop for Windowed<S, W, P> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(Window {
+            slot: Some(generation),
+            ..
+        }) = self.window
+        {
+            self.recloser.inner.release_slot(generation);
+        }
+    }
+}
+
+impl<S, T, E, W, P> Stream for Windowed<S, W, P>
+where
+    S: Stream<Item = Result<T, E>>,
+    W: WaitStrategy,
+    P: ErrorPredicate<E>,
+{
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let guard = &epoch::pin();
+        let mut this = self.project();
+        let recloser = &this.recloser.inner;
+
+        loop {
+            if let Some(pause) = this.pause.as_mut() {
+                ready!(pause.as_mut().poll(cx));
+                *this.pause = None;
+            }
+
+            if this.window.is_none() {
+                let remaining = recloser.open_remaining(guard);
+                let Some(permit) = recloser.permit(guard) else {
+                    let wait = remaining.unwrap_or(MIN_BACKOFF).max(MIN_BACKOFF);
+                    *this.pause = Some(Box::pin(tokio::time::sleep(wait)));
+                    continue;
+                };
+                *this.window = Some(Window {
+                    seen: 0,
+                    verdict: Verdict::Ignored,
+                    slot: permit.into_slot(),
+                });
+            }
+
+            let window = this.window.as_mut().unwrap();
+            return match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(item) => {
+                    window.seen += 1;
+                    window.verdict.observe(&recloser.predicate, &item);
+                    if window.seen == *this.size {
+                        record(recloser, this.window, guard);
+                    }
+                    Poll::Ready(Some(item))
+                }
+                None => {
+                    if window.seen > 0 {
+                        record(recloser, this.window, guard);
+                    }
+                    Poll::Ready(None)
+                }
+            };
+        }
+    }
+}
+
+/// Ends the current window, recording it as classified.
+fn record<W: WaitStrategy, P>(
+    recloser: &Recloser<W, P>,
+    window: &mut Option<Window>,
+    guard: &Guard,
+) {
+    if let Some(Window { verdict, slot, .. }) = window.take() {
+        match verdict {
+            Verdict::Ignored => {
+                recloser.on_classified(Outcome::Ignore, guard);
+            }
+            Verdict::Success => recloser.on_success(guard),
+            Verdict::Failure {
+                weight,
+                category,
+                description,
+            } => {
+                if recloser.failed(weight, category, guard) {
+                    recloser.keep_trip_error(description);
+                }
+            }
+            Verdict::Fuse { description } => {
+                if recloser.on_fuse(guard) {
+                    recloser.keep_trip_error(description);
+                }
+            }
+        }
+        if let Some(generation) = slot {
+            recloser.release_slot(generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::future::poll_fn;
+    use std::time::Duration;
+
+    use fake_clock::FakeClock;
+
+    use super::*;
+    use crate::recloser::CircuitState;
+
+    struct Iter(VecDeque<Result<usize, usize>>);
+
+    impl Stream for Iter {
+        type Item = Result<usize, usize>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.pop_front())
+        }
+    }
+
+    async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn windowed_backoff() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        let items = [Ok(1), Err(2), Err(3), Ok(4), Ok(5), Ok(6)];
+        let mut stream = recloser.windowed(Iter(items.into_iter().collect()), 2);
+
+        // Two failed windows trip the breaker
+        for i in 1..=4 {
+            assert!(matches!(next(&mut stream).await, Some(Ok(n) | Err(n)) if n == i));
+        }
+
+        // Pauses instead of terminating while in Open(_) state
+        let res = tokio::time::timeout(Duration::from_secs(10), next(&mut stream)).await;
+        assert!(res.is_err());
+
+        FakeClock::advance_time(1500);
+        assert!(matches!(next(&mut stream).await, Some(Ok(5))));
+        assert!(matches!(next(&mut stream).await, Some(Ok(6))));
+        assert!(next(&mut stream).await.is_none());
+        assert!(recloser.inner.permit(&epoch::pin()).is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn windowed_classified() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(1))
+            .fuse_wait(Duration::from_secs(60))
+            .default_predicate(
+                AnyError
+                    .fuse_on(|n: &usize| *n == 401)
+                    .ignore_on(|n: &usize| *n == 404),
+            )
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        let items = [Err(500), Err(404), Err(404), Err(404), Err(500), Err(401)];
+        let mut stream = recloser.windowed(Iter(items.into_iter().collect()), 2);
+
+        // A window of ignored errors is not recorded as a second failure
+        for _ in 0..4 {
+            assert!(next(&mut stream).await.is_some());
+        }
+        assert_eq!(CircuitState::Closed, recloser.inner.state());
+
+        // A fuse error blows the fuse regardless of the other items
+        for _ in 0..2 {
+            assert!(next(&mut stream).await.is_some());
+        }
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            recloser.inner.time_until_half_open()
+        );
+    }
+
+    #[tokio::test]
+    async fn events() {
+        let recloser = AsyncRecloser::from(Recloser::default());
+        let mut events = recloser.events();
+
+        let consumer = tokio::spawn(async move {
+            let mut states = Vec::new();
+            while let Some(event) = next(&mut events).await {
+                states.push(event.to);
+            }
+            states
+        });
+        tokio::task::yield_now().await;
+
+        recloser.inner.force_open();
+        recloser.inner.reset();
+        drop(recloser);
+        assert_eq!(
+            vec![CircuitState::ForcedOpen, CircuitState::Closed],
+            consumer.await.unwrap()
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::time::{Duration, SystemTime};
+
+use crate::recloser::{CircuitState, Recloser};
+
+/// What a `Receiver` does with a new event when it is full, see `Recloser::subscribe(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Discards the oldest pending event to make room for the new one.
+    DropOldest,
+    /// Discards the new event.
+    DropNewest,
+}
+
+/// Why a `Recloser` transitioned, see `TransitionEvent::cause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+#[non_exhaustive]
+pub enum TransitionCause {
+    /// The failure_rate crossed the threshold, in either direction, or a policy such
+    /// as `rate_jump(...)` or `slo(...)` tripped.
+    FailureRate,
+    /// A call was permitted once the open wait elapsed.
+    OpenWait,
+    /// An error classified as a fuse by the `ErrorPredicate`.
+    Fuse,
+    /// A call classified as `Outcome::Fatal` by a `Classifier`.
+    Fatal,
+    /// Reopening too many times escalated to `ForcedOpen` state.
+    Escalation,
+    /// Reopening too many times within a window latched `ForcedOpen` state, see
+    /// `RecloserBuilder::flap_latch(...)`.
+    Flapping,
+    /// The failure rate of this category of errors crossed its own threshold, see
+    /// `RecloserBuilder::category(...)`.
+    Category(&'static str),
+    /// The named clause of the `TripPolicy` fired, see `RecloserBuilder::trip_policy(...)`.
+    Policy(&'static str),
+    /// An operator action such as `trip()`, `force_open()` or `reset()`.
+    Manual,
+    /// `HalfOpen(_)` state lasted longer than `RecloserBuilder::half_open_ttl(...)`.
+    Expired,
+}
+
+/// A transition between two different `CircuitState`s of a `Recloser`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct TransitionEvent {
+    pub from: CircuitState,
+    pub to: CircuitState,
+    /// When the transition happened.
+    pub at: SystemTime,
+    pub cause: TransitionCause,
+    /// Failure rate of the calls recorded in the `from` state, `None` if there were none
+    /// or if calls were rejected.
+    pub failure_rate: Option<f32>,
+    /// Number of calls the `failure_rate` was calculated over.
+    pub calls: usize,
+    /// Time spent in the `from` state.
+    pub elapsed: Duration,
+}
+
+struct Queue {
+    inner: Mutex<Pending>,
+    ready: Condvar,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: AtomicU64,
+}
+
+struct Pending {
+    events: VecDeque<TransitionEvent>,
+    /// Whether the `Recloser` was dropped, no more events will come.
+    closed: bool,
+    /// The task polling the `Receiver` as a `Stream`.
+    #[cfg(feature = "stream")]
+    waker: Option<std::task::Waker>,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, Pending> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn push(&self, event: TransitionEvent) {
+        let mut pending = self.lock();
+        if pending.events.len() >= self.capacity {
+            self.dropped.fetch_add(1, Relaxed);
+            match self.overflow {
+                Overflow::DropNewest => return,
+                Overflow::DropOldest => {
+                    pending.events.pop_front();
+                }
+            }
+        }
+        pending.events.push_back(event);
+        #[cfg(feature = "stream")]
+        let waker = pending.waker.take();
+        drop(pending);
+        self.ready.notify_one();
+        #[cfg(feature = "stream")]
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn close(&self) {
+        let mut pending = self.lock();
+        pending.closed = true;
+        #[cfg(feature = "stream")]
+        let waker = pending.waker.take();
+        drop(pending);
+        self.ready.notify_all();
+        #[cfg(feature = "stream")]
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Receives the `TransitionEvent`s of a `Recloser`, see `Recloser::subscribe(...)`.
+///
+/// Events are buffered up to a fixed capacity, so that a slow consumer never blocks
+/// the calls guarded by the `Recloser`.
+pub struct Receiver {
+    queue: Arc<Queue>,
+}
+
+impl Receiver {
+    /// Returns the next pending event, if any, without blocking.
+    pub fn try_recv(&self) -> Option<TransitionEvent> {
+        self.queue.lock().events.pop_front()
+    }
+
+    /// Blocks until an event is received, returns `None` once the `Recloser` is dropped
+    /// and all pending events were received.
+    pub fn recv(&self) -> Option<TransitionEvent> {
+        let pending = self.queue.lock();
+        let mut pending = self
+            .queue
+            .ready
+            .wait_while(pending, |p| p.events.is_empty() && !p.closed)
+            .unwrap_or_else(|e| e.into_inner());
+        pending.events.pop_front()
+    }
+
+    /// Same as `recv()`, but also returns `None` once `timeout` elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<TransitionEvent> {
+        let pending = self.queue.lock();
+        let (mut pending, _) = self
+            .queue
+            .ready
+            .wait_timeout_while(pending, timeout, |p| p.events.is_empty() && !p.closed)
+            .unwrap_or_else(|e| e.into_inner());
+        pending.events.pop_front()
+    }
+
+    /// Returns the number of events discarded so far because the `Receiver` was full.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Relaxed)
+    }
+}
+
+impl Iterator for Receiver {
+    type Item = TransitionEvent;
+
+    fn next(&mut self) -> Option<TransitionEvent> {
+        self.recv()
+    }
+}
+
+/// With the `stream` feature, a `Receiver` is also a `Stream`, see `AsyncRecloser::events()`.
+#[cfg(feature = "stream")]
+impl futures_core::Stream for Receiver {
+    type Item = TransitionEvent;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<TransitionEvent>> {
+        let mut pending = self.queue.lock();
+        match pending.events.pop_front() {
+            Some(event) => std::task::Poll::Ready(Some(event)),
+            None if pending.closed => std::task::Poll::Ready(None),
+            None => {
+                pending.waker = Some(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Receiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.queue.capacity)
+            .field("overflow", &self.queue.overflow)
+            .finish()
+    }
+}
+
+/// The `Receiver`s subscribed to a `Recloser`.
+#[derive(Default)]
+pub(crate) struct Subscribers(Mutex<Vec<Weak<Queue>>>);
+
+impl Subscribers {
+    fn lock(&self) -> MutexGuard<'_, Vec<Weak<Queue>>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn publish(&self, event: TransitionEvent) {
+        self.lock().retain(|queue| match queue.upgrade() {
+            Some(queue) => {
+                queue.push(event);
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl Drop for Subscribers {
+    fn drop(&mut self) {
+        for queue in self.lock().iter().filter_map(Weak::upgrade) {
+            queue.close();
+        }
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Subscribers({})", self.lock().len())
+    }
+}
+
+/// The last transitions of a `Recloser`, see `RecloserBuilder::history(...)`.
+#[derive(Debug)]
+pub(crate) struct History {
+    capacity: usize,
+    events: Mutex<VecDeque<TransitionEvent>>,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize) -> Self {
+        History {
+            capacity: capacity.max(1),
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<TransitionEvent>> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn push(&self, event: TransitionEvent) {
+        let mut events = self.lock();
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+impl<W, P> Recloser<W, P> {
+    /// Returns the last transitions, oldest first, when enabled with
+    /// `RecloserBuilder::history(...)`. Returns an empty `Vec` otherwise.
+    pub fn history(&self) -> Vec<TransitionEvent> {
+        match &self.history {
+            Some(history) => history.lock().iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns a `Receiver` of the transitions happening from now on, buffering up to
+    /// `capacity` events for a background consumer, and applying `overflow` beyond.
+    pub fn subscribe(&self, capacity: usize, overflow: Overflow) -> Receiver {
+        let queue = Arc::new(Queue {
+            inner: Mutex::new(Pending {
+                events: VecDeque::with_capacity(capacity),
+                closed: false,
+                #[cfg(feature = "stream")]
+                waker: None,
+            }),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+            overflow,
+            dropped: AtomicU64::new(0),
+        });
+        self.subscribers.lock().push(Arc::downgrade(&queue));
+        Receiver { queue }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn flap(recloser: &Recloser) {
+        recloser.force_open();
+        recloser.reset();
+    }
+
+    #[test]
+    fn subscribe() {
+        let recloser = Recloser::default();
+        let oldest = recloser.subscribe(2, Overflow::DropOldest);
+        let newest = recloser.subscribe(2, Overflow::DropNewest);
+
+        flap(&recloser);
+        flap(&recloser);
+
+        let to = |receiver: &Receiver| receiver.try_recv().map(|event| event.to);
+        assert_eq!(Some(CircuitState::ForcedOpen), to(&oldest));
+        assert_eq!(Some(CircuitState::Closed), to(&oldest));
+        assert_eq!(None, to(&oldest));
+        assert_eq!(2, oldest.dropped());
+
+        let event = newest.try_recv().unwrap();
+        assert_eq!(
+            (CircuitState::Closed, CircuitState::ForcedOpen),
+            (event.from, event.to)
+        );
+        assert_eq!(Some(CircuitState::Closed), to(&newest));
+        assert_eq!(2, newest.dropped());
+
+        drop(newest);
+        flap(&recloser);
+        assert!(oldest.recv().unwrap().failure_rate.is_none());
+        assert_eq!(1, recloser.subscribers.lock().len());
+
+        let consumer = thread::spawn(move || oldest.count());
+        drop(recloser);
+        assert_eq!(1, consumer.join().unwrap());
+    }
+
+    #[test]
+    fn history() {
+        let recloser = Recloser::custom().closed_len(2).history(3).build();
+        assert!(Recloser::default().history().is_empty());
+
+        for _ in 0..3 {
+            let _ = recloser.call(|| Err::<(), ()>(()));
+        }
+        recloser.force_open();
+        recloser.reset();
+
+        let history = recloser.history();
+        let transitions = history
+            .iter()
+            .map(|event| (event.from, event.to, event.cause, event.failure_rate))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    CircuitState::Closed,
+                    CircuitState::Open,
+                    TransitionCause::FailureRate,
+                    Some(1.0)
+                ),
+                (
+                    CircuitState::Open,
+                    CircuitState::ForcedOpen,
+                    TransitionCause::Manual,
+                    None
+                ),
+                (
+                    CircuitState::ForcedOpen,
+                    CircuitState::Closed,
+                    TransitionCause::Manual,
+                    None
+                ),
+            ],
+            transitions
+        );
+        assert_eq!(2, history[0].calls);
+        assert_eq!(Duration::ZERO, history[1].elapsed);
+        assert!(history[0].at <= history[2].at);
+
+        recloser.force_open();
+        let history = recloser.history();
+        assert_eq!(3, history.len());
+        assert_eq!(
+            (CircuitState::Open, CircuitState::ForcedOpen),
+            (history[0].from, history[0].to)
+        );
+    }
+}
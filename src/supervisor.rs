@@ -0,0 +1,191 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crossbeam_epoch as epoch;
+use tokio::task::JoinHandle;
+
+use crate::error::ErrorPredicate;
+use crate::r#async::AsyncRecloser;
+use crate::wait::WaitStrategy;
+
+/// Minimum delay between two permission checks while in `Open(_)` state.
+pub(crate) const MIN_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Maximum delay between two permission checks while all the probes are taken.
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+impl<W, P> AsyncRecloser<W, P>
+where
+    W: WaitStrategy + Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    /// Spawns a supervisor task that repeatedly spawns a task from `factory`.
+    ///
+    /// Each completion is recorded into the `Recloser`: `Ok(_)` as a success,
+    /// `Err(_)` as judged by its `ErrorPredicate` and a panic as a failure. While in
+    /// `Open(_)` state no task is spawned, the supervisor waits until calls are
+    /// permitted again. While all the probes are taken it waits for the next
+    /// transition, checking again with an exponential backoff.
+    ///
+    /// Aborting the returned `JoinHandle` also aborts the currently running task.
+    pub fn spawn_supervised<F, Fut, T, E>(&self, mut factory: F) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+        P: ErrorPredicate<E>,
+    {
+        let recloser = self.clone();
+        let mut states = self.state_watch();
+
+        tokio::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+            loop {
+                let remaining = recloser.inner.open_remaining(&epoch::pin());
+                let Some(_permit) = recloser.inner.permit(&epoch::pin()) else {
+                    match remaining {
+                        Some(wait) => tokio::time::sleep(wait.max(MIN_BACKOFF)).await,
+                        None => {
+                            let _ = tokio::time::timeout(backoff, states.changed()).await;
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                    }
+                    continue;
+                };
+                backoff = MIN_BACKOFF;
+
+                let mut task = AbortOnDrop(tokio::spawn(factory()));
+                let res = (&mut task.0).await;
+
+                match res {
+                    Ok(Ok(_)) => recloser.inner.on_success(&epoch::pin()),
+                    Ok(Err(e)) => {
+                        recloser
+                            .inner
+                            .on_err(&recloser.inner.predicate, &e, &epoch::pin());
+                    }
+                    Err(e) if e.is_panic() => recloser.inner.on_error(&epoch::pin()),
+                    Err(_) => return,
+                }
+            }
+        })
+    }
+}
+
+/// Aborts the wrapped task when dropped.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use std::sync::Arc;
+
+    use fake_clock::FakeClock;
+
+    use super::*;
+    use crate::recloser::Recloser;
+
+    #[tokio::test(start_paused = true)]
+    async fn supervised_respawn() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let handle = recloser.spawn_supervised(move || {
+            counter.fetch_add(1, Relaxed);
+            async { Err::<(), ()>(()) }
+        });
+
+        // Two failures fill the ring buffer and trip the breaker
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(2, spawned.load(Relaxed));
+
+        // Resumes after the open wait, then trips again on failed probes
+        FakeClock::advance_time(1500);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(4, spawned.load(Relaxed));
+
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn supervised_panics() {
+        let recloser = AsyncRecloser::from(Recloser::custom().closed_len(1).build());
+
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let handle = recloser.spawn_supervised(move || {
+            counter.fetch_add(1, Relaxed);
+            async { panic!("boom") as Result<(), ()> }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(2, spawned.load(Relaxed));
+        assert!(recloser.inner.permit(&epoch::pin()).is_none());
+
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn supervised_predicate() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .default_predicate(|err: &bool| *err)
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let handle = recloser.spawn_supervised(move || {
+            let spawned = counter.fetch_add(1, Relaxed) + 1;
+            async move {
+                if spawned == 3 {
+                    std::future::pending::<()>().await;
+                }
+                Err::<(), _>(false)
+            }
+        });
+
+        // Errors not considered as failures never trip the breaker
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(3, spawned.load(Relaxed));
+        assert!(recloser.inner.permit(&epoch::pin()).is_some());
+
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn supervised_backoff() {
+        let recloser = Recloser::custom()
+            .closed_len(1)
+            .half_open_len(1)
+            .half_open_max_calls(1)
+            .open_wait(Duration::from_secs(1))
+            .build();
+        let recloser = AsyncRecloser::from(recloser);
+        recloser.trip();
+
+        // Takes the only probe, the supervisor cannot get one
+        FakeClock::advance_time(1500);
+        let probe = recloser.inner.permit(&epoch::pin());
+        assert!(probe.is_some());
+
+        let handle = recloser.spawn_supervised(|| async { Ok::<(), ()>(()) });
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert!(recloser.inner.metrics().rejected < 20);
+
+        handle.abort();
+    }
+}
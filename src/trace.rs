@@ -0,0 +1,281 @@
+#[cfg(test)]
+use fake_clock::FakeClock as Instant;
+#[cfg(not(test))]
+use std::time::Instant;
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crossbeam_epoch as epoch;
+
+use crate::error::{Error, ErrorPredicate};
+use crate::events::CallOutcome;
+use crate::recloser::{CircuitState, Recloser, RecloserBuilder};
+use crate::wait::WaitStrategy;
+
+/// A call recorded in a trace, written as a single `<at> <outcome> <latency>` line
+/// with nanosecond times and `s`, `f`, `r` or `i` outcomes. The actual outcome of
+/// a `Rejected` call is unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// Time elapsed since the start of the trace.
+    pub at: Duration,
+    pub outcome: CallOutcome,
+    pub latency: Duration,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.outcome {
+            CallOutcome::Success => 's',
+            CallOutcome::Failure => 'f',
+            CallOutcome::Rejected => 'r',
+            CallOutcome::Ignored => 'i',
+        };
+        write!(
+            f,
+            "{} {} {}",
+            self.at.as_nanos(),
+            outcome,
+            self.latency.as_nanos()
+        )
+    }
+}
+
+impl FromStr for Event {
+    type Err = io::Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || io::Error::new(io::ErrorKind::InvalidData, format!("invalid event: {line}"));
+        let nanos = |s: Option<&str>| s.and_then(|s| s.parse().ok()).map(Duration::from_nanos);
+
+        let mut fields = line.split_whitespace();
+        let at = nanos(fields.next()).ok_or_else(invalid)?;
+        let outcome = match fields.next() {
+            Some("s") => CallOutcome::Success,
+            Some("f") => CallOutcome::Failure,
+            Some("r") => CallOutcome::Rejected,
+            Some("i") => CallOutcome::Ignored,
+            _ => return Err(invalid()),
+        };
+        let latency = nanos(fields.next()).ok_or_else(invalid)?;
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Event {
+            at,
+            outcome,
+            latency,
+        })
+    }
+}
+
+/// Reads the `Event`s of a trace written by a `TraceRecorder`, skipping empty lines.
+pub fn read_trace<R: BufRead>(reader: R) -> io::Result<Vec<Event>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| line?.parse())
+        .collect()
+}
+
+/// Records the outcomes of the calls performed through it as a trace, to be replayed
+/// later with `RecloserBuilder::replay(...)`.
+///
+/// ```rust,ignore
+/// use recloser::{Recloser, TraceRecorder};
+///
+/// let recloser = Recloser::default();
+/// let recorder = TraceRecorder::new(std::fs::File::create("payments.trace")?);
+///
+/// let res = recorder.call(&recloser, || Ok::<(), ()>(()));
+/// ```
+#[derive(Debug)]
+pub struct TraceRecorder<Wr> {
+    origin: Instant,
+    out: Mutex<Wr>,
+}
+
+impl<Wr: Write> TraceRecorder<Wr> {
+    pub fn new(out: Wr) -> Self {
+        TraceRecorder {
+            origin: Instant::now(),
+            out: Mutex::new(out),
+        }
+    }
+
+    /// Same as `Recloser::call(...)`, also records the outcome of the call as classified
+    /// by the default predicate. Fails silently when the trace cannot be written.
+    pub fn call<W, P, F, T, E>(&self, recloser: &Recloser<W, P>, f: F) -> Result<T, Error<E>>
+    where
+        W: WaitStrategy,
+        P: ErrorPredicate<E>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let start = Instant::now();
+        let res = recloser.call(f);
+        let outcome = match &res {
+            Ok(_) => CallOutcome::Success,
+            Err(Error::Inner(err)) if recloser.predicate.is_ignored(err) => CallOutcome::Ignored,
+            Err(Error::Inner(err)) if recloser.predicate.is_err(err) => CallOutcome::Failure,
+            Err(Error::Inner(_)) => CallOutcome::Success,
+            Err(Error::Rejected) => CallOutcome::Rejected,
+        };
+        let _ = self.record(outcome, Instant::now() - start);
+        res
+    }
+
+    /// Records a call performed elsewhere, for instance through an `AsyncRecloser`.
+    pub fn record(&self, outcome: CallOutcome, latency: Duration) -> io::Result<()> {
+        let event = Event {
+            at: Instant::now() - self.origin,
+            outcome,
+            latency,
+        };
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(out, "{event}")
+    }
+
+    pub fn into_inner(self) -> Wr {
+        self.out.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Statistics of a trace replayed by `RecloserBuilder::replay(...)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Replay {
+    /// Calls that would have been permitted.
+    pub permitted: usize,
+    /// Calls that would have been rejected.
+    pub rejected: usize,
+    /// Transitions to `Open(_)` or `ForcedOpen` state.
+    pub trips: usize,
+    /// Events skipped because they were rejected when recorded.
+    pub skipped: usize,
+}
+
+impl<W: WaitStrategy, P> RecloserBuilder<W, P> {
+    /// Builds a `Recloser` and feeds it with the `events` of a trace, following their
+    /// timing, to see how this configuration would have behaved. Time based settings,
+    /// such as `error_rate_jump(...)` or `slo(...)`, follow the timing of the trace too.
+    pub fn replay(self, events: impl IntoIterator<Item = Event>) -> Replay {
+        let recloser = self.build();
+        let guard = &epoch::pin();
+        let mut replay = Replay::default();
+        let mut state = recloser.state();
+
+        for event in events {
+            recloser.skew_to(event.at);
+            match event.outcome {
+                CallOutcome::Rejected => replay.skipped += 1,
+                _ if recloser.permit(guard).is_none() => replay.rejected += 1,
+                CallOutcome::Success => {
+                    replay.permitted += 1;
+                    recloser.on_success(guard);
+                }
+                CallOutcome::Failure => {
+                    replay.permitted += 1;
+                    recloser.on_error(guard);
+                }
+                CallOutcome::Ignored => replay.permitted += 1,
+            }
+
+            let next = recloser.state();
+            if next != state && matches!(next, CircuitState::Open | CircuitState::ForcedOpen) {
+                replay.trips += 1;
+            }
+            state = next;
+        }
+        replay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake_clock::FakeClock;
+
+    use super::*;
+
+    #[test]
+    fn record_and_replay() {
+        let recloser = Recloser::custom().closed_len(1).build();
+        let recorder = TraceRecorder::new(Vec::new());
+
+        let _ = recorder.call(&recloser, || Ok::<(), ()>(()));
+        FakeClock::advance_time(10);
+        let _ = recorder.call(&recloser, || Err::<(), ()>(()));
+        let _ = recorder.call(&recloser, || Err::<(), ()>(()));
+        recorder
+            .record(CallOutcome::Success, Duration::from_millis(3))
+            .unwrap();
+
+        let trace = String::from_utf8(recorder.into_inner()).unwrap();
+        assert_eq!(
+            "0 s 0\n10000000 f 0\n10000000 r 0\n10000000 s 3000000\n",
+            trace
+        );
+
+        let mut events = read_trace(trace.as_bytes()).unwrap();
+        let replay = Recloser::custom()
+            .error_rate(0.9)
+            .closed_len(2)
+            .replay(events.clone());
+        assert_eq!(
+            Replay {
+                permitted: 3,
+                rejected: 0,
+                trips: 0,
+                skipped: 1,
+            },
+            replay
+        );
+
+        // Trips on the failure, then rejects calls until the open wait elapsed
+        events.push(Event {
+            at: Duration::from_secs(31),
+            outcome: CallOutcome::Success,
+            latency: Duration::ZERO,
+        });
+        let replay = Recloser::custom()
+            .closed_len(1)
+            .open_wait(Duration::from_secs(30))
+            .replay(events);
+        assert_eq!(
+            Replay {
+                permitted: 3,
+                rejected: 1,
+                trips: 1,
+                skipped: 1,
+            },
+            replay
+        );
+
+        let ignored = read_trace("12 i 0".as_bytes()).unwrap();
+        assert_eq!(CallOutcome::Ignored, ignored[0].outcome);
+        assert!(read_trace("12 x 0".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn replay_in_trace_time() {
+        let event = |secs, outcome| Event {
+            at: Duration::from_secs(secs),
+            outcome,
+            latency: Duration::ZERO,
+        };
+        let mut events = vec![event(0, CallOutcome::Success); 3];
+        events.push(event(60, CallOutcome::Failure));
+
+        // The failure_rate rises a minute later in the trace, which is not a jump
+        let replay = Recloser::custom()
+            .error_rate(0.9)
+            .closed_len(2)
+            .error_rate_jump(0.5, Duration::from_secs(10))
+            .replay(events);
+        assert_eq!(0, replay.trips);
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+/// Information available to a `WaitStrategy` when transitioning to `Open(_)` state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WaitContext {
+    /// Number of consecutive transitions from `HalfOpen(_)` back to `Open(_)` state,
+    /// `0` when transitioning from `Closed(_)` state.
+    pub reopens: usize,
+    /// The category of the failed call that caused the transition, as returned by
+    /// `ErrorPredicate::category(...)`, so that for instance rate limited calls wait
+    /// longer than refused connections. `None` when it was not categorized.
+    pub category: Option<&'static str>,
+}
+
+impl WaitContext {
+    pub(crate) fn new(reopens: usize) -> Self {
+        WaitContext {
+            reopens,
+            category: None,
+        }
+    }
+
+    pub(crate) fn with_category(mut self, category: Option<&'static str>) -> Self {
+        self.category = category;
+        self
+    }
+}
+
+/// A trait used to determine how long a `Recloser` stays in `Open(_)` state.
+pub trait WaitStrategy {
+    fn open_wait(&self, ctx: &WaitContext) -> Duration;
+}
+
+impl<F> WaitStrategy for F
+where
+    F: Fn(&WaitContext) -> Duration,
+{
+    fn open_wait(&self, ctx: &WaitContext) -> Duration {
+        self(ctx)
+    }
+}
+
+/// A boxed `WaitStrategy`, for when the strategy is chosen at runtime.
+pub type BoxedWait = Box<dyn WaitStrategy + Send + Sync>;
+
+impl WaitStrategy for BoxedWait {
+    fn open_wait(&self, ctx: &WaitContext) -> Duration {
+        (**self).open_wait(ctx)
+    }
+}
+
+/// Always waits for the same duration, as set by `RecloserBuilder::open_wait(...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultWait(pub Duration);
+
+impl Default for DefaultWait {
+    fn default() -> Self {
+        DefaultWait(Duration::from_secs(30))
+    }
+}
+
+impl WaitStrategy for DefaultWait {
+    #[inline]
+    fn open_wait(&self, _ctx: &WaitContext) -> Duration {
+        self.0
+    }
+}
+
+/// Waits for the durations of an explicit schedule, indexed by the number of
+/// consecutive reopens and clamped at its last duration.
+///
+/// With the `serde` feature, it (de)serializes as a list of seconds, for instance
+/// `[1, 5, 30, 300]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Schedule {
+    #[cfg_attr(feature = "serde", serde(with = "secs"))]
+    waits: Vec<Duration>,
+}
+
+impl Schedule {
+    /// An empty schedule never waits.
+    pub fn new(waits: impl IntoIterator<Item = Duration>) -> Self {
+        Schedule {
+            waits: waits.into_iter().collect(),
+        }
+    }
+
+    pub fn waits(&self) -> &[Duration] {
+        &self.waits
+    }
+}
+
+impl WaitStrategy for Schedule {
+    fn open_wait(&self, ctx: &WaitContext) -> Duration {
+        let last = self.waits.len().saturating_sub(1);
+        self.waits
+            .get(ctx.reopens.min(last))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod secs {
+    use std::time::Duration;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(waits: &[Duration], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(waits.iter().map(Duration::as_secs_f64))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Duration>, D::Error> {
+        Vec::<f64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule() {
+        let schedule = Schedule::new([1, 5, 30].map(Duration::from_secs));
+        let waits = (0..5)
+            .map(|reopens| schedule.open_wait(&WaitContext::new(reopens)).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 5, 30, 30, 30], waits);
+
+        let empty = Schedule::new([]);
+        assert_eq!(Duration::ZERO, empty.open_wait(&WaitContext::new(3)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_schedule() {
+        let schedule: Schedule = serde_json::from_str("[1, 0.5, 300]").unwrap();
+        assert_eq!(
+            &[1000, 500, 300_000].map(Duration::from_millis),
+            schedule.waits()
+        );
+        assert_eq!("[1.0,0.5,300.0]", serde_json::to_string(&schedule).unwrap());
+        assert!(serde_json::from_str::<Schedule>("[-1]").is_err());
+    }
+}